@@ -0,0 +1,74 @@
+// 빌드 정보를 컴파일 시점 환경 변수로 넣음 (examples/common/health.rs의 BUILD_INFO가 읽음)
+//   GIT_COMMIT       git rev-parse --short=12 HEAD (+ "-dirty"), git이 없으면 설정하지 않음
+//   BUILD_TIMESTAMP  RFC 3339 UTC, SOURCE_DATE_EPOCH가 있으면 그 값 (재현 가능한 빌드)
+//
+// 사용: Cargo.toml 옆에 복사하면 cargo가 빌드 전에 자동으로 실행
+// 커밋이 바뀌거나 build.rs가 바뀔 때만 다시 실행됨 → 타임스탬프도 그때 갱신
+
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    if let Some(commit) = git(&["rev-parse", "--short=12", "HEAD"]) {
+        let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+            .is_some_and(|changes| !changes.is_empty());
+        let suffix = if dirty { "-dirty" } else { "" };
+        println!("cargo:rustc-env=GIT_COMMIT={}{}", commit, suffix);
+    }
+
+    // 커밋/체크아웃(logs/HEAD)이나 스테이징(index)이 바뀌면 다시 실행
+    for path in ["logs/HEAD", "index"] {
+        if let Some(file) = git(&["rev-parse", "--git-path", path]) {
+            println!("cargo:rerun-if-changed={}", file);
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs())
+        });
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={}", rfc3339(secs));
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+// 외부 크레이트 없이 UNIX 초 → "2024-05-01T12:34:56Z"
+fn rfc3339(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // 1970-01-01 기준 일수 → 연/월/일 (그레고리력)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
@@ -5,73 +5,391 @@
 // tokio = { version = "1", features = ["full"] }
 // serde = { version = "1", features = ["derive"] }
 // serde_json = "1"
+// utoipa = "5"
+// utoipa-swagger-ui = { version = "8", features = ["axum", "vendored"] }
+// tower = { version = "0.4", features = ["util"] }
+// hmac = "0.12"
+// sha2 = "0.10"
+// base64 = "0.22"
+// getrandom = "0.2"
+//
+// [dev-dependencies]
+// http-body-util = "0.1"
+//
+// 서버 실행(주소 설정, 안전한 종료)은 examples/common/server.rs 사용
+// 상태 확인/빌드 정보는 examples/common/health.rs 사용
+// 빌드 정보(git 커밋, 빌드 시각): chapter07/build.rs를 Cargo.toml 옆에 복사
+// 페이지 커서 서명 키: CURSOR_SECRET=<32바이트 이상> cargo run (없으면 실행할 때마다 임의 키)
+
+// common 중 서버 실행, 상태 확인 부분만 포함 (나머지는 이 예제에 필요 없음)
+// Shutdown 등 다른 예제용 항목은 쓰지 않으므로 dead_code 허용
+#[path = "common/server.rs"]
+#[allow(dead_code)]
+mod server;
+
+#[path = "common/health.rs"]
+#[allow(dead_code)]
+mod health;
 
 use axum::{
-    extract::{Path, Query},
-    routing::{get, post},
+    extract::{OriginalUri, Path, Query, Request, State},
+    http::{
+        header::{ACCEPT, LINK, LOCATION, VARY},
+        HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{any, get, MethodRouter},
     Json, Router,
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use health::{BuildInfo, Readiness, ReadinessReport};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use server::ServerConfig;
+use sha2::Sha256;
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use tower::ServiceExt;
+use utoipa::openapi::{self, Deprecated};
+use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
 #[tokio::main]
-async fn main() {
-    // 라우터 설정
-    let app = Router::new()
-        // 기본 라우트
-        .route("/", get(root))
-        .route("/hello/:name", get(hello))
-        // Users API
-        .route("/users", get(list_users).post(create_user))
-        .route("/users/:id", get(get_user))
-        // Nested routes
-        .nest("/api", api_routes());
+async fn main() -> ExitCode {
+    // 주소: --host/--port 또는 HOST/PORT (기본 0.0.0.0:3000)
+    let config = match ServerConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match CursorKey::from_env() {
+        Ok(key) => {
+            let _ = CURSOR_KEY.set(key);
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
 
-    println!("Server running at http://localhost:3000");
+    println!("Docs: http://localhost:{}/docs", config.port);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    // 포트가 사용 중이면 패닉 대신 에러 메시지 + 종료 코드 1
+    if let Err(e) = server::serve(app(), &config).await {
+        eprintln!("Error: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+// 경로와 핸들러 목록: 라우터와 스펙 검사 테스트가 같이 씀
+// (라우터는 등록된 경로 목록을 공개하지 않음)
+type RouteTable<S = ()> = Vec<(&'static str, MethodRouter<S>)>;
+
+fn router<S: Clone + Send + Sync + 'static>(table: RouteTable<S>) -> Router<S> {
+    table
+        .into_iter()
+        .fold(Router::new(), |router, (path, method)| {
+            router.route(path, method)
+        })
+}
+
+// nest 접두사 (테스트도 이 값으로 전체 경로를 만듦)
+const API_PREFIX: &str = "/api";
+const V1_PREFIX: &str = "/v1";
+const V2_PREFIX: &str = "/v2";
+
+// 라우터 설정
+fn app() -> Router {
+    router(root_table())
+        // Nested routes
+        .nest(API_PREFIX, api_routes())
+        // API 문서 (/docs, /openapi.json)
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", ApiDoc::openapi()))
+}
+
+fn root_table() -> RouteTable {
+    vec![
+        // 기본 라우트
+        ("/", get(root)),
+        ("/hello/:name", get(hello)),
+        // Users API
+        ("/users", get(list_users).post(create_user)),
+        ("/users/:id", get(get_user)),
+    ]
 }
 
 // API 서브 라우터
+//   /api/v1/users, /api/v2/users  경로로 버전 지정
+//   /api/users                    API-Version 또는 Accept 헤더로 버전 선택
+//   /api/health, /api/version     운영용이라 버전 없음
 fn api_routes() -> Router {
+    let versions = VersionedRouters {
+        v1: v1_routes(),
+        v2: v2_routes(),
+    };
+
+    let negotiated = router(negotiated_table()).with_state(versions.clone());
+
     Router::new()
-        .route("/health", get(health_check))
-        .route("/version", get(version))
+        .nest(V1_PREFIX, versions.v1)
+        .nest(V2_PREFIX, versions.v2)
+        .merge(negotiated)
+        .merge(router(health_table()).with_state(readiness_checks()))
+}
+
+fn negotiated_table() -> RouteTable<VersionedRouters> {
+    vec![("/users", any(negotiate)), ("/users/:id", any(negotiate))]
+}
+
+// v1: 최상위 /users와 같은 응답 (폐기 예정)
+fn v1_routes() -> Router {
+    router(v1_table()).layer(middleware::map_response(|response| async move {
+        ApiVersion::V1.annotate(response)
+    }))
+}
+
+fn v1_table() -> RouteTable {
+    vec![
+        ("/users", get(list_users).post(create_user)),
+        ("/users/:id", get(get_user)),
+    ]
+}
+
+// v2: 목록은 data/meta/links로 감싸고 생성은 201 + Location
+// 응답 모양이 같은 get_user는 v1과 공유
+fn v2_routes() -> Router {
+    router(v2_table()).layer(middleware::map_response(|response| async move {
+        ApiVersion::V2.annotate(response)
+    }))
+}
+
+fn v2_table() -> RouteTable {
+    vec![
+        ("/users", get(list_users_v2).post(create_user_v2)),
+        ("/users/:id", get(get_user)),
+    ]
+}
+
+fn health_table() -> RouteTable<Readiness> {
+    vec![
+        // /health는 예전 경로 (liveness와 같음)
+        ("/health", get(liveness)),
+        ("/health/live", get(liveness)),
+        ("/health/ready", get(readiness)),
+        ("/version", get(version)),
+    ]
+}
+
+// 이 예제는 DB 같은 외부 의존성이 없어서 검사가 없음
+// (rest_api.rs는 DB 검사를 등록, 백그라운드 작업은 health::Heartbeat로 등록)
+fn readiness_checks() -> Readiness {
+    Readiness::new()
+}
+
+// ========================================
+// API 버전
+// ========================================
+
+const API_VERSION: HeaderName = HeaderName::from_static("api-version");
+
+// Accept: application/vnd.example.v2+json
+const VENDOR_PREFIX: &str = "application/vnd.example.v";
+const VENDOR_SUFFIX: &str = "+json";
+
+// v1 폐기 일정 (Deprecation: RFC 9745, Sunset: RFC 8594)
+const V1_DEPRECATION: &str = "@1788220800"; // 2026-09-01
+const V1_SUNSET: &str = "Mon, 01 Mar 2027 00:00:00 GMT";
+const V1_SUCCESSOR: &str = r#"</api/v2/users>; rel="successor-version""#;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    // 버전을 고르지 않은 기존 클라이언트가 깨지지 않도록 가장 오래된 버전
+    const DEFAULT: ApiVersion = ApiVersion::V1;
+
+    fn parse(number: &str) -> Option<Self> {
+        match number.trim() {
+            "1" => Some(ApiVersion::V1),
+            "2" => Some(ApiVersion::V2),
+            _ => None,
+        }
+    }
+
+    fn number(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "1",
+            ApiVersion::V2 => "2",
+        }
+    }
+
+    // API-Version 헤더 > Accept의 vendor 타입 > 기본값
+    // 지원하지 않는 버전: API-Version은 400, Accept는 406
+    fn negotiate(headers: &HeaderMap) -> Result<Self, (StatusCode, Json<serde_json::Value>)> {
+        if let Some(value) = headers.get(API_VERSION) {
+            return value
+                .to_str()
+                .ok()
+                .and_then(Self::parse)
+                .ok_or_else(|| unsupported_version(StatusCode::BAD_REQUEST));
+        }
+
+        let media_types = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|range| range.split(';').next().unwrap_or_default().trim());
+        for media_type in media_types {
+            let number = media_type
+                .strip_prefix(VENDOR_PREFIX)
+                .and_then(|rest| rest.strip_suffix(VENDOR_SUFFIX));
+            if let Some(number) = number {
+                return Self::parse(number)
+                    .ok_or_else(|| unsupported_version(StatusCode::NOT_ACCEPTABLE));
+            }
+        }
+
+        Ok(Self::DEFAULT)
+    }
+
+    // 모든 응답에 버전 표시, 폐기된 버전은 종료 일정과 후속 버전 링크
+    fn annotate(self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        headers.insert(API_VERSION, HeaderValue::from_static(self.number()));
+        if self == ApiVersion::V1 {
+            headers.insert("deprecation", HeaderValue::from_static(V1_DEPRECATION));
+            headers.insert("sunset", HeaderValue::from_static(V1_SUNSET));
+            headers.insert(LINK, HeaderValue::from_static(V1_SUCCESSOR));
+        }
+        response
+    }
+}
+
+fn unsupported_version(status: StatusCode) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        status,
+        Json(json!({ "error": "Unsupported API version (supported: 1, 2)" })),
+    )
+}
+
+#[derive(Clone)]
+struct VersionedRouters {
+    v1: Router,
+    v2: Router,
+}
+
+// /api/users...: 헤더로 고른 버전의 라우터에 요청을 그대로 넘김
+async fn negotiate(State(versions): State<VersionedRouters>, request: Request) -> Response {
+    let mut response = match ApiVersion::negotiate(request.headers()) {
+        Ok(version) => {
+            let router = match version {
+                ApiVersion::V1 => versions.v1,
+                ApiVersion::V2 => versions.v2,
+            };
+            router
+                .oneshot(forwarded(request))
+                .await
+                .unwrap_or_else(|never| match never {})
+        }
+        Err(rejection) => rejection.into_response(),
+    };
+    // 같은 URL이라도 헤더에 따라 응답이 다름 → 캐시가 구분하도록
+    response
+        .headers_mut()
+        .insert(VARY, HeaderValue::from_static("accept, api-version"));
+    response
+}
+
+// 바깥 라우터가 넣은 경로 파라미터(:id)가 안쪽 라우터의 것과 겹치지 않도록
+// 확장(extensions)을 비운 새 요청으로 넘김 (링크를 만드는 OriginalUri만 유지)
+fn forwarded(request: Request) -> Request {
+    let (parts, body) = request.into_parts();
+    let mut forwarded = Request::new(body);
+    *forwarded.method_mut() = parts.method;
+    *forwarded.uri_mut() = parts.uri;
+    *forwarded.version_mut() = parts.version;
+    *forwarded.headers_mut() = parts.headers;
+    if let Some(original) = parts.extensions.get::<OriginalUri>() {
+        forwarded.extensions_mut().insert(original.clone());
+    }
+    forwarded
 }
 
 // ========================================
 // 7-1. 기본 핸들러
 // ========================================
 
+#[utoipa::path(get, path = "/", responses((status = 200, body = String)))]
 async fn root() -> &'static str {
     "Hello, Axum!"
 }
 
-async fn health_check() -> &'static str {
-    "OK"
+// liveness: 프로세스가 응답하는지만 (의존성 검사 없음)
+#[utoipa::path(
+    get,
+    path = "/api/health/live",
+    responses((status = 200, description = r#"{"status": "ok"}"#, content_type = "application/json"))
+)]
+async fn liveness() -> Json<serde_json::Value> {
+    health::live().await
 }
 
-async fn version() -> Json<Version> {
-    Json(Version {
-        version: "1.0.0".to_string(),
-        name: "Rust API".to_string(),
-    })
+// readiness: 등록된 검사를 모두 실행, 검사마다 상태와 소요 시간
+#[utoipa::path(
+    get,
+    path = "/api/health/ready",
+    responses(
+        (status = 200, description = "All checks passed", content_type = "application/json"),
+        (status = 503, description = "At least one check failed", content_type = "application/json")
+    )
+)]
+async fn readiness(State(readiness): State<Readiness>) -> ReadinessReport {
+    readiness.run().await
 }
 
-#[derive(Serialize)]
-struct Version {
-    version: String,
-    name: String,
+// 크레이트 버전, git 커밋, 빌드 시각 (컴파일 시점에 포함)
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    responses((status = 200, description = "Build information", content_type = "application/json"))
+)]
+async fn version() -> Json<BuildInfo> {
+    health::version().await
 }
 
 // ========================================
 // 7-3. Path 파라미터
 // ========================================
 
+#[utoipa::path(
+    get,
+    path = "/hello/{name}",
+    params(("name" = String, Path)),
+    responses((status = 200, body = String))
+)]
 async fn hello(Path(name): Path<String>) -> String {
     format!("Hello, {}!", name)
 }
 
+#[utoipa::path(
+    get,
+    path = "/users/{id}",
+    params(("id" = u32, Path)),
+    responses((status = 200, body = User))
+)]
 async fn get_user(Path(id): Path<u32>) -> Json<User> {
     // 실제로는 DB에서 조회
     Json(User {
@@ -85,68 +403,361 @@ async fn get_user(Path(id): Path<u32>) -> Json<User> {
 // 7-3. Query 파라미터
 // ========================================
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct Pagination {
+    /// 페이지 번호 (1부터, cursor와 함께 쓸 수 없음, 0이면 400)
     page: Option<u32>,
+    /// 페이지 크기 (기본 10, 1~100 밖이면 400)
     limit: Option<u32>,
+    /// 정렬 기준: id, name, email (앞에 '-'를 붙이면 내림차순)
     sort: Option<String>,
+    /// 이전 응답의 next_cursor 또는 prev_cursor (정렬 기준이 들어 있어 sort는 생략 가능)
+    cursor: Option<String>,
 }
 
-async fn list_users(Query(params): Query<Pagination>) -> Json<UserList> {
-    let page = params.page.unwrap_or(1);
-    let limit = params.limit.unwrap_or(10);
-    let sort = params.sort.unwrap_or_else(|| "id".to_string());
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 100;
 
-    // 실제로는 DB에서 조회
-    let users = vec![
-        User {
-            id: 1,
-            name: "Alice".to_string(),
-            email: "alice@example.com".to_string(),
-        },
-        User {
-            id: 2,
-            name: "Bob".to_string(),
-            email: "bob@example.com".to_string(),
-        },
+// 정렬 기준: "name" = 오름차순, "-name" = 내림차순
+#[derive(Clone, Copy)]
+enum SortField {
+    Id,
+    Name,
+    Email,
+}
+
+struct Sort {
+    field: SortField,
+    descending: bool,
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, key) = match s.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, s),
+        };
+
+        let field = match key {
+            "id" => SortField::Id,
+            "name" => SortField::Name,
+            "email" => SortField::Email,
+            _ => {
+                return Err(format!(
+                    "Unknown sort field '{}' (allowed: id, name, email)",
+                    key
+                ))
+            }
+        };
+
+        Ok(Sort { field, descending })
+    }
+}
+
+impl Sort {
+    // 목록에서의 위치 (정렬 값, id): 정렬 값이 같으면 id 순이라 위치가 하나로 정해짐
+    fn position<'a>(&self, user: &'a User) -> (&'a str, u32) {
+        let key = match self.field {
+            SortField::Id => "", // id만으로 정렬
+            SortField::Name => &user.name,
+            SortField::Email => &user.email,
+        };
+        (key, user.id)
+    }
+
+    fn compare(&self, a: (&str, u32), b: (&str, u32)) -> Ordering {
+        let ordering = a.cmp(&b);
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+
+    fn apply(&self, users: &mut [User]) {
+        users.sort_by(|a, b| self.compare(self.position(a), self.position(b)));
+    }
+}
+
+// 실제로는 DB에서 조회
+fn sample_users() -> Vec<User> {
+    let names = [
+        "Alice", "Bob", "Charlie", "Dave", "Eve", "Frank", "Grace", "Heidi", "Ivan", "Judy",
+        "Mallory", "Niaj", "Olivia", "Peggy", "Rupert", "Sybil", "Trent", "Victor", "Walter",
+        "Zoe",
     ];
 
-    Json(UserList {
-        users,
+    // id 순서와 이름 순서가 다르도록 섞어서 생성
+    names
+        .iter()
+        .rev()
+        .zip(1..)
+        .map(|(name, id)| User {
+            id,
+            name: name.to_string(),
+            email: format!("{}@example.com", name.to_lowercase()),
+        })
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/users",
+    params(Pagination),
+    responses(
+        (status = 200, body = UserList),
+        (status = 400, description = "Unknown sort field, invalid cursor, or page/limit out of range")
+    )
+)]
+async fn list_users(
+    OriginalUri(uri): OriginalUri,
+    Query(params): Query<Pagination>,
+) -> Result<Json<UserList>, (StatusCode, Json<serde_json::Value>)> {
+    let bad_request = |msg: String| (StatusCode::BAD_REQUEST, Json(json!({ "error": msg })));
+    // 범위 밖의 값은 조용히 고치지 않고 400 (요청한 것과 다른 페이지를 주지 않도록)
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(bad_request(format!(
+            "limit must be between 1 and {}",
+            MAX_LIMIT
+        )));
+    }
+    if params.page == Some(0) {
+        return Err(bad_request("page starts at 1".to_string()));
+    }
+
+    // 서명이 맞지 않는 커서는 400
+    let cursor = params
+        .cursor
+        .as_deref()
+        .map(|token| {
+            cursor_key()
+                .decode(token)
+                .ok_or_else(|| bad_request("Invalid cursor".to_string()))
+        })
+        .transpose()?;
+    if cursor.is_some() && params.page.is_some() {
+        return Err(bad_request(
+            "Use either cursor or page, not both".to_string(),
+        ));
+    }
+    let sort_param = match (&cursor, params.sort) {
+        (Some(cursor), Some(sort)) if sort != cursor.sort => {
+            return Err(bad_request(format!(
+                "Cursor was issued for sort={}",
+                cursor.sort
+            )))
+        }
+        (Some(cursor), _) => cursor.sort.clone(),
+        (None, sort) => sort.unwrap_or_else(|| "id".to_string()),
+    };
+
+    // 알 수 없는 정렬 키는 400
+    let sort: Sort = sort_param.parse().map_err(bad_request)?;
+
+    let mut users = sample_users();
+    sort.apply(&mut users);
+
+    let total = users.len() as u32;
+    // 커서를 쓰면 페이지 번호는 알 수 없음 (null)
+    let page = cursor.is_none().then(|| params.page.unwrap_or(1));
+    let offset = page.map_or(0, |page| (page as usize - 1) * limit as usize);
+    let range = page_range(&users, &sort, cursor.as_ref(), offset, limit as usize);
+
+    // 이번 페이지의 마지막 다음 / 첫 항목 앞 (더 없으면 null)
+    let at = |user: &User, before: bool| {
+        cursor_key().encode(&Cursor {
+            sort: sort_param.clone(),
+            key: sort.position(user).0.to_string(),
+            id: user.id,
+            before,
+        })
+    };
+    let next_cursor = users[range.clone()]
+        .last()
+        .filter(|_| range.end < users.len())
+        .map(|user| at(user, false));
+    let prev_cursor = users[range.clone()]
+        .first()
+        .filter(|_| range.start > 0)
+        .map(|user| at(user, true));
+
+    // 이전/다음 페이지 링크 (없으면 null), 요청한 경로 기준 (/users, /api/v1/users, ...)
+    // 커서로 요청했으면 링크도 커서, 아니면 페이지 번호
+    let (next, prev) = match page {
+        Some(page) => {
+            let link = |page: u32| {
+                format!(
+                    "{}?page={}&limit={}&sort={}",
+                    uri.path(),
+                    page,
+                    limit,
+                    sort_param
+                )
+            };
+            // 끝을 넘은 페이지의 prev는 마지막 페이지로 (page - 1도 비어 있으므로)
+            let last_page = users.len().div_ceil(limit as usize).max(1) as u32;
+            (
+                (range.end < users.len()).then(|| link(page + 1)),
+                (page > 1).then(|| link((page - 1).min(last_page))),
+            )
+        }
+        None => {
+            let link =
+                |cursor: &String| format!("{}?cursor={}&limit={}", uri.path(), cursor, limit);
+            (
+                next_cursor.as_ref().map(link),
+                prev_cursor.as_ref().map(link),
+            )
+        }
+    };
+
+    Ok(Json(UserList {
+        users: users[range].to_vec(),
         page,
         limit,
-        sort,
-        total: 2,
-    })
+        sort: sort_param,
+        total,
+        next,
+        prev,
+        next_cursor,
+        prev_cursor,
+    }))
+}
+
+// ========================================
+// 커서 페이지네이션
+// ========================================
+
+// page=N은 앞 페이지에 추가/삭제가 생기면 항목이 밀려서 겹치거나 빠짐 (DB의 OFFSET은 건너뛸 행도 다 읽음)
+// 커서 = 마지막으로 본 위치 (정렬 값, id) → "그 위치 다음부터"
+//   SQL: WHERE (name, id) > (?, ?) ORDER BY name, id LIMIT ?
+// 클라이언트가 위치를 바꾸지 못하도록 HMAC-SHA256으로 서명 (base64라 내용은 읽을 수 있음)
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    sort: String,
+    key: String,
+    id: u32,
+    // true: 이 위치 앞의 페이지 (prev_cursor)
+    before: bool,
+}
+
+const MIN_SECRET_LEN: usize = 32;
+
+// 서버를 여러 대 띄우면 모두 같은 CURSOR_SECRET이어야 다른 서버가 만든 커서도 통과
+static CURSOR_KEY: OnceLock<CursorKey> = OnceLock::new();
+
+// main에서 설정하지 않았으면(테스트) 임의 키
+fn cursor_key() -> &'static CursorKey {
+    CURSOR_KEY.get_or_init(CursorKey::random)
+}
+
+struct CursorKey(Vec<u8>);
+
+impl CursorKey {
+    // CURSOR_SECRET이 없으면 임의 키 (재시작하면 이전 커서는 400 → 첫 페이지부터 다시)
+    fn from_env() -> Result<Self, String> {
+        match std::env::var("CURSOR_SECRET") {
+            Ok(secret) if secret.len() < MIN_SECRET_LEN => Err(format!(
+                "CURSOR_SECRET must be at least {} bytes",
+                MIN_SECRET_LEN
+            )),
+            Ok(secret) => Ok(CursorKey(secret.into_bytes())),
+            Err(_) => Ok(CursorKey::random()),
+        }
+    }
+
+    fn random() -> Self {
+        let mut key = vec![0; MIN_SECRET_LEN];
+        getrandom::getrandom(&mut key).expect("OS random number generator");
+        CursorKey(key)
+    }
+
+    fn mac(&self, payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts any key length");
+        mac.update(payload);
+        mac
+    }
+
+    // base64url(JSON).base64url(서명)
+    fn encode(&self, cursor: &Cursor) -> String {
+        let payload = serde_json::to_vec(cursor).expect("cursor serializes to JSON");
+        let signature = self.mac(&payload).finalize().into_bytes();
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(&payload),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    // 형식이 틀리거나 서명이 맞지 않으면 None (서명은 상수 시간 비교)
+    fn decode(&self, token: &str) -> Option<Cursor> {
+        let (payload, signature) = token.split_once('.')?;
+        let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(&payload).verify_slice(&signature).ok()?;
+        serde_json::from_slice(&payload).ok()
+    }
+}
+
+// 정렬된 목록에서 이번 페이지의 범위 (커서가 없으면 offset부터)
+// 커서의 사용자가 그 사이 삭제되어도 위치(정렬 값, id)는 그대로 비교할 수 있음
+fn page_range(
+    users: &[User],
+    sort: &Sort,
+    cursor: Option<&Cursor>,
+    offset: usize,
+    limit: usize,
+) -> Range<usize> {
+    let Some(cursor) = cursor else {
+        let start = offset.min(users.len());
+        return start..(start + limit).min(users.len());
+    };
+    let at = (cursor.key.as_str(), cursor.id);
+    if cursor.before {
+        let end = users.partition_point(|u| sort.compare(sort.position(u), at) == Ordering::Less);
+        end.saturating_sub(limit)..end
+    } else {
+        let start =
+            users.partition_point(|u| sort.compare(sort.position(u), at) != Ordering::Greater);
+        start..(start + limit).min(users.len())
+    }
 }
 
 // ========================================
 // 7-4. JSON 요청/응답
 // ========================================
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, ToSchema)]
 struct User {
     id: u32,
     name: String,
     email: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct UserList {
     users: Vec<User>,
-    page: u32,
+    page: Option<u32>,
     limit: u32,
     sort: String,
     total: u32,
+    next: Option<String>,
+    prev: Option<String>,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 struct CreateUser {
     name: String,
     email: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 struct CreateUserResponse {
     id: u32,
     name: String,
@@ -154,6 +765,12 @@ struct CreateUserResponse {
     message: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/users",
+    request_body = CreateUser,
+    responses((status = 200, body = CreateUserResponse))
+)]
 async fn create_user(Json(payload): Json<CreateUser>) -> Json<CreateUserResponse> {
     // 실제로는 DB에 저장
     Json(CreateUserResponse {
@@ -163,3 +780,634 @@ async fn create_user(Json(payload): Json<CreateUser>) -> Json<CreateUserResponse
         message: "User created successfully".to_string(),
     })
 }
+
+// ========================================
+// v2 응답
+// ========================================
+
+// 목록을 data/meta/links로 감쌈 (v1은 평평한 UserList)
+#[derive(Serialize, ToSchema)]
+struct UserPage {
+    data: Vec<User>,
+    meta: PageMeta,
+    links: PageLinks,
+}
+
+#[derive(Serialize, ToSchema)]
+struct PageMeta {
+    page: Option<u32>,
+    limit: u32,
+    sort: String,
+    total: u32,
+    next_cursor: Option<String>,
+    prev_cursor: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct PageLinks {
+    next: Option<String>,
+    prev: Option<String>,
+}
+
+impl From<UserList> for UserPage {
+    fn from(list: UserList) -> Self {
+        UserPage {
+            data: list.users,
+            meta: PageMeta {
+                page: list.page,
+                limit: list.limit,
+                sort: list.sort,
+                total: list.total,
+                next_cursor: list.next_cursor,
+                prev_cursor: list.prev_cursor,
+            },
+            links: PageLinks {
+                next: list.next,
+                prev: list.prev,
+            },
+        }
+    }
+}
+
+// 페이지 계산은 v1 핸들러를 그대로 쓰고 모양만 바꿈
+#[utoipa::path(
+    get,
+    path = "/users",
+    params(Pagination),
+    responses(
+        (status = 200, body = UserPage),
+        (status = 400, description = "Unknown sort field")
+    )
+)]
+async fn list_users_v2(
+    uri: OriginalUri,
+    params: Query<Pagination>,
+) -> Result<Json<UserPage>, (StatusCode, Json<serde_json::Value>)> {
+    let Json(list) = list_users(uri, params).await?;
+    Ok(Json(list.into()))
+}
+
+// v1의 message 필드 대신 201 + Location
+#[utoipa::path(
+    post,
+    path = "/users",
+    request_body = CreateUser,
+    responses((status = 201, body = User, headers(("location" = String))))
+)]
+async fn create_user_v2(Json(payload): Json<CreateUser>) -> impl IntoResponse {
+    // 실제로는 DB에 저장
+    let user = User {
+        id: 1,
+        name: payload.name,
+        email: payload.email,
+    };
+    let location = format!("/api/v2/users/{}", user.id);
+    (StatusCode::CREATED, [(LOCATION, location)], Json(user))
+}
+
+// ========================================
+// OpenAPI 문서
+// ========================================
+
+// 핸들러의 #[utoipa::path]와 타입의 ToSchema를 모아서 스펙 생성
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        root,
+        hello,
+        list_users,
+        get_user,
+        create_user,
+        liveness,
+        readiness,
+        version
+    ),
+    components(schemas(User, UserList, CreateUser, CreateUserResponse)),
+    nest(
+        (path = "/api/v1", api = V1Doc, tags = ["v1"]),
+        (path = "/api/v2", api = V2Doc, tags = ["v2"])
+    )
+)]
+struct ApiDoc;
+
+// 핸들러를 공유하므로 operationId에 버전을 붙여 스펙 안에서 겹치지 않게 함
+// (modifiers는 식별자만 받아서 상수로 둠)
+const V1_OPERATIONS: ApiVersion = ApiVersion::V1;
+const V2_OPERATIONS: ApiVersion = ApiVersion::V2;
+
+#[derive(OpenApi)]
+#[openapi(paths(list_users, get_user, create_user), modifiers(&V1_OPERATIONS))]
+struct V1Doc;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_users_v2, get_user, create_user_v2),
+    components(schemas(UserPage, PageMeta, PageLinks)),
+    modifiers(&V2_OPERATIONS)
+)]
+struct V2Doc;
+
+impl Modify for ApiVersion {
+    fn modify(&self, spec: &mut openapi::OpenApi) {
+        for item in spec.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation.operation_id = operation
+                    .operation_id
+                    .take()
+                    .map(|id| format!("v{}_{}", self.number(), id));
+                if *self == ApiVersion::V1 {
+                    operation.deprecated = Some(Deprecated::True);
+                }
+            }
+        }
+    }
+}
+
+// ========================================
+// 테스트
+// ========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn get_json(uri: &str) -> (StatusCode, Value) {
+        let (status, body) = send("GET", uri).await;
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    async fn send(method: &str, uri: &str) -> (StatusCode, Vec<u8>) {
+        send_body(method, uri, "{}").await
+    }
+
+    async fn send_body(method: &str, uri: &str, body: &str) -> (StatusCode, Vec<u8>) {
+        let (status, _, bytes) = send_with_headers(method, uri, &[], body).await;
+        (status, bytes)
+    }
+
+    async fn send_with_headers(
+        method: &str,
+        uri: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> (StatusCode, HeaderMap, Vec<u8>) {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, headers, bytes.to_vec())
+    }
+
+    async fn get_versioned(uri: &str, headers: &[(&str, &str)]) -> (StatusCode, HeaderMap, Value) {
+        let (status, headers, body) = send_with_headers("GET", uri, headers, "").await;
+        (
+            status,
+            headers,
+            serde_json::from_slice(&body).unwrap_or(Value::Null),
+        )
+    }
+
+    // 문서에 넣지 않는 라우트
+    //   /api/health: /api/health/live의 예전 이름
+    //   /api/users*: Accept 헤더로 /api/v1, /api/v2 중 하나로 보냄 (둘 다 문서에 있음)
+    const UNDOCUMENTED_ROUTES: &[&str] = &[
+        "/api/health",
+        "/api/users",
+        "/api/users/:id",
+        "/docs",
+        "/openapi.json",
+    ];
+
+    // app()과 api_routes()가 쓰는 것과 같은 목록에 nest 접두사를 붙인 전체 경로
+    fn routed_paths() -> Vec<String> {
+        fn under<S>(prefix: &str, table: RouteTable<S>) -> Vec<String> {
+            table
+                .into_iter()
+                .map(|(path, _)| format!("{}{}", prefix, path))
+                .collect()
+        }
+        let v1 = format!("{}{}", API_PREFIX, V1_PREFIX);
+        let v2 = format!("{}{}", API_PREFIX, V2_PREFIX);
+
+        [
+            under("", root_table()),
+            under(&v1, v1_table()),
+            under(&v2, v2_table()),
+            under(API_PREFIX, negotiated_table()),
+            under(API_PREFIX, health_table()),
+        ]
+        .concat()
+    }
+
+    // 스펙에 있는 모든 (경로, 메서드)가 실제 라우터에 등록되어 있어야 하고,
+    // 라우터에 있는 모든 (경로, 메서드)는 허용 목록에 없으면 스펙에 있어야 함
+    #[tokio::test]
+    async fn openapi_spec_matches_routes() {
+        let spec = ApiDoc::openapi();
+
+        let routes = routed_paths();
+        assert!(routes.contains(&"/api/v2/users/:id".to_string()));
+        for route in &routes {
+            if UNDOCUMENTED_ROUTES.contains(&route.as_str()) {
+                continue;
+            }
+            // :id → {id} (스펙 표기), 요청에는 임의 값
+            let path = route
+                .split('/')
+                .map(|seg| match seg.strip_prefix(':') {
+                    Some(name) => format!("{{{}}}", name),
+                    None => seg.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            let uri = route
+                .split('/')
+                .map(|seg| if seg.starts_with(':') { "1" } else { seg })
+                .collect::<Vec<_>>()
+                .join("/");
+            let item = spec.paths.paths.get(&path);
+
+            for method in ["GET", "POST", "PUT", "DELETE"] {
+                let (status, _) = send(method, &uri).await;
+                if status == StatusCode::NOT_FOUND || status == StatusCode::METHOD_NOT_ALLOWED {
+                    continue;
+                }
+                let documented = item.is_some_and(|item| match method {
+                    "GET" => item.get.is_some(),
+                    "POST" => item.post.is_some(),
+                    "PUT" => item.put.is_some(),
+                    _ => item.delete.is_some(),
+                });
+                assert!(
+                    documented,
+                    "{} {} is routed but not documented",
+                    method, route
+                );
+            }
+        }
+
+        for (path, item) in &spec.paths.paths {
+            let uri = path
+                .split('/')
+                .map(|seg| if seg.starts_with('{') { "1" } else { seg })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            let methods = [
+                ("GET", item.get.is_some()),
+                ("POST", item.post.is_some()),
+                ("PUT", item.put.is_some()),
+                ("DELETE", item.delete.is_some()),
+            ];
+            for (method, _) in methods.into_iter().filter(|(_, documented)| *documented) {
+                let (status, _) = send(method, &uri).await;
+                assert!(
+                    status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+                    "{} {} is documented but not routed",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_openapi_31_document() {
+        let (status, body) = send("GET", "/openapi.json").await;
+        let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3.1"));
+        assert!(spec["components"]["schemas"]["UserList"].is_object());
+        assert!(spec["paths"]["/users"]["get"]["parameters"].is_array());
+    }
+
+    #[tokio::test]
+    async fn basic_routes() {
+        let (status, body) = send("GET", "/").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"Hello, Axum!");
+
+        let (status, body) = send("GET", "/hello/Rust").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"Hello, Rust!");
+    }
+
+    #[tokio::test]
+    async fn nested_api_routes() {
+        for uri in ["/api/health", "/api/health/live"] {
+            let (status, body) = get_json(uri).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["status"], "ok");
+        }
+
+        let (status, body) = get_json("/api/health/ready").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert!(body["checks"].is_array());
+
+        let (status, body) = get_json("/api/version").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert!(body["git_commit"].is_string());
+        assert!(body["build_timestamp"].is_string());
+    }
+
+    #[tokio::test]
+    async fn versions_have_their_own_list_shape() {
+        let (status, headers, v1) = get_versioned("/api/v1/users?limit=5", &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers["api-version"], "1");
+        assert_eq!(v1["total"], 20);
+        assert_eq!(v1["users"].as_array().unwrap().len(), 5);
+        assert_eq!(v1["next"], "/api/v1/users?page=2&limit=5&sort=id");
+
+        let (status, headers, v2) = get_versioned("/api/v2/users?limit=5", &[]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers["api-version"], "2");
+        assert_eq!(v2["meta"]["total"], 20);
+        assert_eq!(v2["data"], v1["users"]);
+        assert_eq!(v2["links"]["next"], "/api/v2/users?page=2&limit=5&sort=id");
+        assert!(v2.get("users").is_none());
+
+        // 모양이 같은 단건 조회는 같은 핸들러
+        let (_, _, one) = get_versioned("/api/v1/users/7", &[]).await;
+        let (_, _, two) = get_versioned("/api/v2/users/7", &[]).await;
+        assert_eq!(one, two);
+    }
+
+    #[tokio::test]
+    async fn versions_have_their_own_create_response() {
+        let body = r#"{"name":"Kim","email":"kim@example.com"}"#;
+
+        let (status, _, v1) = send_with_headers("POST", "/api/v1/users", &[], body).await;
+        let v1: Value = serde_json::from_slice(&v1).unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(v1["message"], "User created successfully");
+
+        let (status, headers, v2) = send_with_headers("POST", "/api/v2/users", &[], body).await;
+        let v2: Value = serde_json::from_slice(&v2).unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(headers[LOCATION], "/api/v2/users/1");
+        assert_eq!(v2["name"], "Kim");
+        assert!(v2.get("message").is_none());
+    }
+
+    #[tokio::test]
+    async fn only_v1_is_deprecated() {
+        let (_, v1, _) = get_versioned("/api/v1/users/1", &[]).await;
+        assert_eq!(v1["deprecation"], V1_DEPRECATION);
+        assert_eq!(v1["sunset"], V1_SUNSET);
+        assert!(v1[LINK].to_str().unwrap().contains("successor-version"));
+
+        let (_, v2, _) = get_versioned("/api/v2/users/1", &[]).await;
+        assert!(v2.get("deprecation").is_none());
+        assert!(v2.get("sunset").is_none());
+
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let v1_list = &spec["paths"]["/api/v1/users"]["get"];
+        assert_eq!(v1_list["deprecated"], true);
+        assert_eq!(v1_list["operationId"], "v1_list_users");
+        assert!(spec["paths"]["/api/v2/users"]["get"]["deprecated"].is_null());
+    }
+
+    #[tokio::test]
+    async fn version_is_negotiated_from_headers() {
+        let v2_requests: [&[(&str, &str)]; 2] = [
+            &[("api-version", "2")],
+            &[("accept", "text/html, application/vnd.example.v2+json;q=0.9")],
+        ];
+        for headers in v2_requests {
+            let (status, response, body) = get_versioned("/api/users?limit=2", headers).await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(response["api-version"], "2");
+            assert_eq!(response[VARY], "accept, api-version");
+            assert_eq!(body["links"]["next"], "/api/users?page=2&limit=2&sort=id");
+        }
+
+        // 헤더가 없으면 기존 클라이언트를 위해 v1
+        let (_, response, body) = get_versioned("/api/users/3", &[("accept", "*/*")]).await;
+        assert_eq!(response["api-version"], "1");
+        assert_eq!(response["deprecation"], V1_DEPRECATION);
+        assert_eq!(body["id"], 3);
+
+        let (status, _, _) = get_versioned("/api/users", &[("api-version", "3")]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let accept = [("accept", "application/vnd.example.v9+json")];
+        let (status, response, _) = get_versioned("/api/users", &accept).await;
+        assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(response[VARY], "accept, api-version");
+    }
+
+    #[tokio::test]
+    async fn get_and_create_user() {
+        let (status, body) = get_json("/users/7").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], 7);
+
+        let (status, body) = send_body(
+            "POST",
+            "/users",
+            r#"{"name":"Kim","email":"kim@example.com"}"#,
+        )
+        .await;
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "Kim");
+    }
+
+    #[tokio::test]
+    async fn list_users_paginates_and_sorts() {
+        let (status, body) = get_json("/users?page=2&limit=5&sort=-name").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 20);
+        assert_eq!(body["users"].as_array().unwrap().len(), 5);
+        assert_eq!(body["users"][0]["name"], "Rupert");
+        assert_eq!(body["prev"], "/users?page=1&limit=5&sort=-name");
+        assert_eq!(body["next"], "/users?page=3&limit=5&sort=-name");
+
+        let (_, last) = get_json("/users?page=4&limit=5").await;
+        assert!(last["next"].is_null());
+
+        // 끝을 넘은 페이지: 비어 있고 prev는 마지막 페이지로
+        let (status, past) = get_json("/users?page=100&limit=5").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(past["users"].as_array().unwrap().is_empty());
+        assert!(past["next"].is_null());
+        assert_eq!(past["prev"], "/users?page=4&limit=5&sort=id");
+        let (_, past) = get_json("/users?page=5&limit=7").await;
+        assert_eq!(past["prev"], "/users?page=3&limit=7&sort=id");
+    }
+
+    fn names(body: &Value) -> Vec<String> {
+        body["users"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|u| u["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn cursors_walk_forward_and_back() {
+        let (_, all) = get_json("/users?limit=100&sort=-name").await;
+        let (_, first) = get_json("/users?limit=6&sort=-name").await;
+        assert!(first["prev_cursor"].is_null());
+
+        // next_cursor를 따라가면 전체 목록과 같은 순서
+        let mut seen = names(&first);
+        let mut pages = vec![first.clone()];
+        let mut cursor = first["next_cursor"].as_str().unwrap().to_string();
+        loop {
+            let (status, page) = get_json(&format!("/users?cursor={}&limit=6", cursor)).await;
+            assert_eq!(status, StatusCode::OK);
+            assert!(page["page"].is_null());
+            seen.extend(names(&page));
+            pages.push(page.clone());
+            match page["next_cursor"].as_str() {
+                Some(next) => {
+                    assert_eq!(page["next"], format!("/users?cursor={}&limit=6", next));
+                    cursor = next.to_string();
+                }
+                None => break,
+            }
+        }
+        assert_eq!(seen, names(&all));
+        assert_eq!(pages.len(), 4);
+        assert!(pages[3]["next"].is_null());
+
+        // 마지막 페이지의 prev_cursor → 세 번째 페이지
+        let prev = pages[3]["prev_cursor"].as_str().unwrap();
+        let (_, back) = get_json(&format!("/users?cursor={}&limit=6", prev)).await;
+        assert_eq!(names(&back), names(&pages[2]));
+
+        // v2는 meta에 커서
+        let (_, v2) = get_json("/api/v2/users?limit=5").await;
+        let next = v2["meta"]["next_cursor"].as_str().unwrap();
+        let (_, v2) = get_json(&format!("/api/v2/users?cursor={}", next)).await;
+        assert_eq!(v2["data"][0]["id"], 6);
+        assert_eq!(
+            v2["links"]["prev"],
+            format!(
+                "/api/v2/users?cursor={}&limit=10",
+                v2["meta"]["prev_cursor"].as_str().unwrap()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn tampered_cursors_are_rejected() {
+        let (_, body) = get_json("/users?limit=5&sort=name").await;
+        let cursor = body["next_cursor"].as_str().unwrap();
+        let (payload, signature) = cursor.split_once('.').unwrap();
+
+        // 위치를 바꾼 커서 (서명은 그대로)
+        let mut forged: Cursor =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).unwrap()).unwrap();
+        forged.id = 1;
+        let forged = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged).unwrap()),
+            signature
+        );
+        // 다른 키로 서명한 커서 (다른 서버, 재시작 전)
+        let other_key = CursorKey::random().encode(&Cursor {
+            sort: "name".to_string(),
+            key: "Alice".to_string(),
+            id: 20,
+            before: false,
+        });
+        for bad in [forged.as_str(), other_key.as_str(), payload, "garbage"] {
+            let (status, body) = get_json(&format!("/users?cursor={}", bad)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "cursor={}", bad);
+            assert_eq!(body["error"], "Invalid cursor");
+        }
+
+        let (status, _) = get_json(&format!("/users?cursor={}&page=2", cursor)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, body) = get_json(&format!("/users?cursor={}&sort=-id", cursor)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Cursor was issued for sort=name");
+        let (status, _) = get_json(&format!("/users?cursor={}&sort=name", cursor)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // 페이지 사이에 추가/삭제가 생겨도 커서는 겹치거나 빠지는 항목이 없음
+    #[test]
+    fn cursor_pages_are_stable_under_writes() {
+        let sort: Sort = "name".parse().unwrap();
+        let mut users = sample_users();
+        sort.apply(&mut users);
+        let expected: Vec<u32> = users[5..10].iter().map(|u| u.id).collect();
+
+        let last = &users[4];
+        let cursor = Cursor {
+            sort: "name".to_string(),
+            key: last.name.clone(),
+            id: last.id,
+            before: false,
+        };
+
+        // 첫 페이지를 읽은 뒤: 앞에 한 명 추가, 첫 페이지에서 두 명 삭제 (커서의 사용자 포함)
+        let removed = [users[0].id, last.id];
+        users.retain(|u| !removed.contains(&u.id));
+        users.push(User {
+            id: 21,
+            name: "Aaron".to_string(),
+            email: "aaron@example.com".to_string(),
+        });
+        sort.apply(&mut users);
+
+        let page = |range: Range<usize>| users[range].iter().map(|u| u.id).collect::<Vec<_>>();
+        assert_eq!(
+            page(page_range(&users, &sort, Some(&cursor), 0, 5)),
+            expected
+        );
+        // 페이지 번호로는 앞이 한 명 줄어서 두 번째 페이지의 첫 사용자를 건너뜀
+        assert_eq!(page(page_range(&users, &sort, None, 5, 5))[0], expected[1]);
+    }
+
+    #[tokio::test]
+    async fn bad_requests_are_rejected() {
+        let (status, _) = get_json("/users?sort=password").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_json("/users/abc").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // 범위 밖의 page/limit은 고치지 않고 거부
+        for query in ["limit=0", "limit=101", "page=0"] {
+            let (status, body) = get_json(&format!("/users?{}", query)).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+            assert!(body["error"].is_string());
+        }
+        let (status, _) = get_json("/users?limit=100").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = send_body("POST", "/users", "{").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_json("/nope").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
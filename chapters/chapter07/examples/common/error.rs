@@ -0,0 +1,246 @@
+// 공통 API 에러 (RFC 7807 application/problem+json)
+//
+// {
+//   "type": "/errors/user_not_found",
+//   "title": "Not Found",
+//   "status": 404,
+//   "detail": "User 3 not found",
+//   "code": "user_not_found",          ← 클라이언트가 분기할 때 쓰는 고정 코드
+//   "request_id": "6f1c...",
+//   "errors": [ { "field": "email", "code": "invalid_format", "message": "..." } ]
+// }
+
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::json;
+use std::borrow::Cow;
+
+use super::request_id;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub code: Cow<'static, str>,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(
+        field: impl Into<String>,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            field: field.into(),
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    // 요청 문제 (4xx)
+    NotFound(&'static str, String), // (code, detail)
+    BadRequest(&'static str, String),
+    Validation(Vec<FieldError>),
+    Conflict(&'static str, String),
+    PreconditionFailed(&'static str, String), // If-Match 불일치
+    UnsupportedMediaType(String),
+    MethodNotAllowed,
+    TooManyRequests(&'static str, u64), // (code, Retry-After 초)
+
+    // 인증/권한
+    MissingToken,
+    InvalidToken,
+    RevokedToken,
+    RefreshTokenReused,
+    InvalidCredentials,
+    Forbidden,
+
+    // 서버 문제 (상세 내용은 로그에만 남김)
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(..) => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(..) => StatusCode::BAD_REQUEST,
+            ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Conflict(..) => StatusCode::CONFLICT,
+            ApiError::PreconditionFailed(..) => StatusCode::PRECONDITION_FAILED,
+            ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiError::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::TooManyRequests(..) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::MissingToken
+            | ApiError::InvalidToken
+            | ApiError::RevokedToken
+            | ApiError::RefreshTokenReused
+            | ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    // 한 번 정한 코드는 바꾸지 않음 (클라이언트 호환성)
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(code, _)
+            | ApiError::BadRequest(code, _)
+            | ApiError::Conflict(code, _)
+            | ApiError::PreconditionFailed(code, _)
+            | ApiError::TooManyRequests(code, _) => code,
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            ApiError::MethodNotAllowed => "method_not_allowed",
+            ApiError::MissingToken => "missing_token",
+            ApiError::InvalidToken => "invalid_token",
+            ApiError::RevokedToken => "token_revoked",
+            ApiError::RefreshTokenReused => "refresh_token_reused",
+            ApiError::InvalidCredentials => "invalid_credentials",
+            ApiError::Forbidden => "forbidden",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    fn detail(&self) -> String {
+        match self {
+            ApiError::NotFound(_, msg)
+            | ApiError::BadRequest(_, msg)
+            | ApiError::Conflict(_, msg)
+            | ApiError::PreconditionFailed(_, msg)
+            | ApiError::UnsupportedMediaType(msg) => msg.clone(),
+            ApiError::Validation(errors) => {
+                format!("{} field(s) failed validation", errors.len())
+            }
+            ApiError::MethodNotAllowed => "Method not allowed for this path".to_string(),
+            ApiError::TooManyRequests(_, secs) => {
+                format!("Too many requests, retry in {} second(s)", secs)
+            }
+            ApiError::MissingToken => "Missing authorization token".to_string(),
+            ApiError::InvalidToken => "Invalid token".to_string(),
+            ApiError::RevokedToken => "Token has been revoked".to_string(),
+            ApiError::RefreshTokenReused => {
+                "Refresh token reuse detected, session revoked".to_string()
+            }
+            ApiError::InvalidCredentials => "Invalid username or password".to_string(),
+            ApiError::Forbidden => "Insufficient permissions".to_string(),
+            ApiError::Internal(_) => "Internal server error".to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let request_id = request_id::current();
+
+        // 요청 span 안이면 request_id가 로그에 함께 남음
+        if let ApiError::Internal(msg) = &self {
+            tracing::error!(error = %msg, "internal error");
+        }
+
+        let mut body = json!({
+            "type": format!("/errors/{}", self.code()),
+            "title": status.canonical_reason().unwrap_or("Error"),
+            "status": status.as_u16(),
+            "detail": self.detail(),
+            "code": self.code(),
+            "request_id": request_id,
+        });
+
+        if let ApiError::Validation(errors) = &self {
+            body["errors"] = json!(errors);
+        }
+
+        let mut response = (status, axum::Json(body)).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+
+        if matches!(
+            self,
+            ApiError::MissingToken | ApiError::InvalidToken | ApiError::RevokedToken
+        ) {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+
+        if let ApiError::TooManyRequests(_, secs) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+
+        response
+    }
+}
+
+// ========================================
+// axum 추출기 에러 → ApiError
+// ========================================
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            // 타입/필드 문제는 필드 단위 에러로
+            JsonRejection::JsonDataError(err) => {
+                ApiError::Validation(vec![json_field_error(&err.body_text())])
+            }
+            JsonRejection::JsonSyntaxError(err) => {
+                ApiError::BadRequest("malformed_json", err.body_text())
+            }
+            JsonRejection::MissingJsonContentType(err) => {
+                ApiError::UnsupportedMediaType(err.body_text())
+            }
+            other => ApiError::BadRequest("invalid_body", other.body_text()),
+        }
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::BadRequest("invalid_path_parameter", rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::BadRequest("invalid_query_parameter", rejection.body_text())
+    }
+}
+
+// "...target type: email: invalid type: ..." / "...: missing field `email` at ..."
+// 메시지에서 필드 이름을 찾고, 못 찾으면 "body"
+pub fn json_field_error(text: &str) -> FieldError {
+    let message = text.split_once("target type: ").map_or(text, |(_, m)| m);
+
+    if let Some(rest) = message.split_once("missing field `").map(|(_, r)| r) {
+        if let Some((field, _)) = rest.split_once('`') {
+            return FieldError::new(field, "required", format!("{} is required", field));
+        }
+    }
+
+    match message.split_once(": ") {
+        Some((path, reason)) if !path.contains(' ') => {
+            FieldError::new(path, "invalid_type", reason)
+        }
+        _ => FieldError::new("body", "invalid_type", message),
+    }
+}
+
+// 등록되지 않은 경로/메서드도 같은 형식으로 응답
+pub async fn not_found() -> ApiError {
+    ApiError::NotFound("route_not_found", "No route for this path".to_string())
+}
+
+pub async fn method_not_allowed() -> ApiError {
+    ApiError::MethodNotAllowed
+}
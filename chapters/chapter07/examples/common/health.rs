@@ -0,0 +1,299 @@
+// 상태 확인과 빌드 정보
+//
+//   liveness : 프로세스가 요청에 응답하는지만 (실패하면 재시작) → 의존성은 검사하지 않음
+//   readiness: 등록된 검사(DB 연결, 백그라운드 작업 등)를 모두 실행 (실패하면 트래픽에서 제외)
+//
+// {
+//   "status": "fail",                                   ← 하나라도 실패하면 fail + 503
+//   "checks": [
+//     { "name": "database", "status": "ok", "latency_ms": 0.4 },
+//     { "name": "cleanup", "status": "fail", "latency_ms": 0.0, "error": "no heartbeat for 95s" }
+//   ]
+// }
+//
+// 빌드 정보는 chapter07/build.rs가 컴파일 시점에 넣은 환경 변수
+// (build.rs 없이 빌드하면 git_commit, build_timestamp는 "unknown")
+
+use axum::{
+    async_trait,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+// 느린 의존성 하나가 readiness 응답 전체를 붙잡지 않도록
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+// ========================================
+// 검사
+// ========================================
+
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    fn name(&self) -> &str;
+    async fn check(&self) -> Result<(), String>;
+}
+
+// 클로저로 만드는 검사
+//   health::check_fn("database", move || { let pool = pool.clone(); async move { ... } })
+pub struct FnCheck<F> {
+    name: String,
+    f: F,
+}
+
+pub fn check_fn<F, Fut>(name: impl Into<String>, f: F) -> FnCheck<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), String>> + Send,
+{
+    FnCheck {
+        name: name.into(),
+        f,
+    }
+}
+
+#[async_trait]
+impl<F, Fut> HealthCheck for FnCheck<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = Result<(), String>> + Send,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        (self.f)().await
+    }
+}
+
+// 백그라운드 작업용: 작업이 주기마다 beat()를 호출, max_age 동안 없으면 실패
+#[derive(Clone)]
+pub struct Heartbeat {
+    name: String,
+    max_age: Duration,
+    last: Arc<Mutex<Instant>>,
+}
+
+impl Heartbeat {
+    pub fn new(name: impl Into<String>, max_age: Duration) -> Self {
+        Self {
+            name: name.into(),
+            max_age,
+            last: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    pub fn beat(&self) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn age(&self) -> Duration {
+        self.last
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+}
+
+#[async_trait]
+impl HealthCheck for Heartbeat {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        let age = self.age();
+        if age > self.max_age {
+            Err(format!("no heartbeat for {}s", age.as_secs()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// ========================================
+// readiness
+// ========================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: Status,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    pub status: Status,
+    pub checks: Vec<CheckResult>,
+}
+
+// 200 또는 503 (로드 밸런서/쿠버네티스는 상태 코드만 봄)
+impl IntoResponse for ReadinessReport {
+    fn into_response(self) -> Response {
+        let status = match self.status {
+            Status::Ok => StatusCode::OK,
+            Status::Fail => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, Json(self)).into_response()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Readiness {
+    checks: Vec<Arc<dyn HealthCheck>>,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, check: impl HealthCheck + 'static) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    // 모든 검사를 동시에 실행, 결과는 등록한 순서로
+    pub async fn run(&self) -> ReadinessReport {
+        let mut tasks = JoinSet::new();
+        for (index, check) in self.checks.iter().cloned().enumerate() {
+            tasks.spawn(async move {
+                let start = Instant::now();
+                let result = tokio::time::timeout(CHECK_TIMEOUT, check.check())
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(format!("timed out after {}s", CHECK_TIMEOUT.as_secs()))
+                    });
+                (index, start.elapsed(), result)
+            });
+        }
+
+        // 패닉한 검사는 결과가 오지 않으므로 실패로 채워 둠
+        let mut checks: Vec<CheckResult> = self
+            .checks
+            .iter()
+            .map(|check| CheckResult {
+                name: check.name().to_string(),
+                status: Status::Fail,
+                latency_ms: 0.0,
+                error: Some("check panicked".to_string()),
+            })
+            .collect();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((index, elapsed, result)) = joined {
+                let slot = &mut checks[index];
+                slot.latency_ms = elapsed.as_secs_f64() * 1000.0;
+                (slot.status, slot.error) = match result {
+                    Ok(()) => (Status::Ok, None),
+                    Err(e) => (Status::Fail, Some(e)),
+                };
+            }
+        }
+
+        let status = if checks.iter().all(|c| c.status == Status::Ok) {
+            Status::Ok
+        } else {
+            Status::Fail
+        };
+        ReadinessReport { status, checks }
+    }
+}
+
+// liveness는 의존성과 상관없이 항상 ok (응답했다는 것 자체가 결과)
+pub async fn live() -> Json<serde_json::Value> {
+    Json(json!({ "status": Status::Ok }))
+}
+
+// ========================================
+// 빌드 정보
+// ========================================
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    pub build_timestamp: &'static str,
+}
+
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    name: env!("CARGO_PKG_NAME"),
+    version: env!("CARGO_PKG_VERSION"),
+    git_commit: match option_env!("GIT_COMMIT") {
+        Some(commit) => commit,
+        None => "unknown",
+    },
+    build_timestamp: match option_env!("BUILD_TIMESTAMP") {
+        Some(timestamp) => timestamp,
+        None => "unknown",
+    },
+};
+
+pub async fn version() -> Json<BuildInfo> {
+    Json(BUILD_INFO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn readiness_fails_if_any_check_fails() {
+        let readiness = Readiness::new()
+            .with(check_fn("storage", || async { Ok(()) }))
+            .with(Heartbeat::new("worker", Duration::ZERO))
+            .with(check_fn("queue", || async {
+                Err("connection refused".to_string())
+            }));
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let report = readiness.run().await;
+
+        assert_eq!(report.status, Status::Fail);
+        let names: Vec<_> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["storage", "worker", "queue"]);
+        assert_eq!(report.checks[0].status, Status::Ok);
+        assert!(report.checks[1]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("no heartbeat"));
+        assert_eq!(
+            report.checks[2].error.as_deref(),
+            Some("connection refused")
+        );
+        assert_eq!(
+            report.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[tokio::test]
+    async fn heartbeat_recovers_after_beat() {
+        let readiness = Readiness::new().with(Heartbeat::new("worker", Duration::from_secs(60)));
+        assert_eq!(readiness.run().await.status, Status::Ok);
+
+        let stale = Heartbeat::new("stale", Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(stale.check().await.is_err());
+        stale.beat();
+        assert!(stale.age() < Duration::from_millis(5));
+    }
+}
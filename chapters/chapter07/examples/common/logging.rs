@@ -0,0 +1,67 @@
+// 구조화 로그 (tracing)
+//
+// 요청마다 span 하나: request_id, method, path, user_id(인증된 경우)
+// 응답이 나가면 그 span 안에서 status, latency_ms 이벤트 기록
+//
+//   RUST_LOG=debug,sqlx=warn   (필터, 기본 info)
+//   LOG_FORMAT=json            (한 줄에 JSON 하나, 기본은 사람이 읽는 형식)
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use std::time::Instant;
+use tracing::{field, Instrument, Span};
+use tracing_subscriber::EnvFilter;
+
+use super::request_id;
+
+// main에서 한 번 호출 (이미 설정되어 있으면 무시)
+pub fn init() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info,sqlx=warn"));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let _ = match std::env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => builder
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .try_init(),
+        _ => builder.try_init(),
+    };
+}
+
+// request_id_middleware 안쪽에 등록 (요청 ID를 span에 넣기 위해)
+//   .layer(middleware::from_fn(trace_request))
+//   .layer(middleware::from_fn(request_id_middleware))
+pub async fn trace_request(req: Request, next: Next) -> Response {
+    // 쿼리 문자열은 토큰 등이 들어갈 수 있어서 path만 기록
+    let span = tracing::info_span!(
+        "request",
+        request_id = request_id::current().as_deref().unwrap_or("-"),
+        method = %req.method(),
+        path = req.uri().path(),
+        user_id = field::Empty,
+    );
+    let start = Instant::now();
+
+    async move {
+        let response = next.run(req).await;
+
+        let status = response.status().as_u16();
+        let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+        if response.status().is_server_error() {
+            tracing::error!(status, latency_ms, "request failed");
+        } else {
+            tracing::info!(status, latency_ms, "request completed");
+        }
+
+        response
+    }
+    .instrument(span)
+    .await
+}
+
+// 인증 미들웨어에서 토큰의 사용자를 현재 요청 span에 기록
+pub fn record_user(user_id: &str) {
+    Span::current().record("user_id", user_id);
+}
@@ -0,0 +1,138 @@
+// Prometheus 메트릭 (/metrics, text 형식)
+//
+//   http_requests_total{method, route, status}              요청 수
+//   http_request_duration_seconds{method, route, status}    응답 시간 히스토그램
+//   http_requests_in_flight                                 처리 중인 요청 수
+//   app_users                                               저장된 사용자 수 (수집할 때 갱신)
+//
+// route 라벨은 실제 URI(/users/42)가 아닌 라우트 템플릿(/users/:id)
+// → 라벨 값 종류가 라우트 수로 제한됨
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::time::Instant;
+
+// 라우트에 매칭되지 않은 요청(404)은 모두 하나의 라벨로
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+// 테스트마다 따로 쓸 수 있도록 전역 레지스트리 대신 앱 상태에 보관
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    in_flight: IntGauge,
+    users: IntGauge,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let labels = &["method", "route", "status"];
+
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Total number of HTTP requests"),
+            labels,
+        )
+        .expect("valid metric");
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            labels,
+        )
+        .expect("valid metric");
+        let in_flight = IntGauge::new(
+            "http_requests_in_flight",
+            "Number of HTTP requests being processed",
+        )
+        .expect("valid metric");
+        let users = IntGauge::new("app_users", "Number of stored users").expect("valid metric");
+
+        let registry = Registry::new();
+        for collector in [
+            Box::new(requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(latency.clone()),
+            Box::new(in_flight.clone()),
+            Box::new(users.clone()),
+        ] {
+            registry.register(collector).expect("unique metric name");
+        }
+
+        Self {
+            registry,
+            requests,
+            latency,
+            in_flight,
+            users,
+        }
+    }
+
+    pub fn set_users(&self, count: usize) {
+        self.users.set(count as i64);
+    }
+
+    // Prometheus text 형식 응답
+    pub fn export(&self) -> Response {
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        if let Err(e) = encoder.encode(&self.registry.gather(), &mut buffer) {
+            tracing::error!(error = %e, "failed to encode metrics");
+        }
+
+        (
+            [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+            buffer,
+        )
+            .into_response()
+    }
+}
+
+// 핸들러가 패닉하거나 연결이 끊겨도 Drop에서 감소
+struct InFlightGuard(IntGauge);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+// Router::layer로 등록하면 라우트마다 적용되어 MatchedPath를 읽을 수 있음
+//   .layer(middleware::from_fn_with_state(metrics, track_metrics))
+pub async fn track_metrics(State(metrics): State<Metrics>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+
+    metrics.in_flight.inc();
+    let _guard = InFlightGuard(metrics.in_flight.clone());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    metrics.requests.with_label_values(&labels).inc();
+    metrics
+        .latency
+        .with_label_values(&labels)
+        .observe(start.elapsed().as_secs_f64());
+
+    response
+}
@@ -0,0 +1,14 @@
+// chapter07 서버 예제들이 함께 쓰는 코드
+// 각 예제에서 `mod common;` 으로 포함 (예제마다 쓰는 항목이 달라서 dead_code 허용)
+#![allow(dead_code)]
+
+pub mod error;
+pub mod extract;
+pub mod health;
+pub mod logging;
+pub mod metrics;
+pub mod rate_limit;
+pub mod request_id;
+pub mod security;
+pub mod server;
+pub mod validation;
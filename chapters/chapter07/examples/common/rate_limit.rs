@@ -0,0 +1,239 @@
+// 요청 제한 (토큰 버킷)
+//
+// 키(클라이언트 IP 또는 로그인한 사용자)마다 버킷 하나
+//   - 처음에는 capacity개가 가득 참, period 동안 capacity개가 일정하게 다시 참
+//   - 요청마다 1개 사용, 비어 있으면 429 + Retry-After
+//
+// 응답 헤더 (IETF RateLimit 헤더 초안)
+//   RateLimit-Limit: 버킷 크기
+//   RateLimit-Remaining: 남은 요청 수
+//   RateLimit-Reset: 버킷이 다시 가득 찰 때까지 남은 초
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::error::ApiError;
+
+// 키가 이만큼 쌓이면 가득 찬(= 오래 안 쓴) 버킷을 정리
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+// ========================================
+// 할당량
+// ========================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub capacity: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub const fn per_minute(capacity: u32) -> Self {
+        Self {
+            capacity,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    // "10/60" = 60초에 10개
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid quota '{}' (expected <count>/<seconds>)", value);
+        let (count, secs) = value.split_once('/').ok_or_else(invalid)?;
+        let capacity: u32 = count.trim().parse().map_err(|_| invalid())?;
+        let secs: u64 = secs.trim().parse().map_err(|_| invalid())?;
+        if capacity == 0 || secs == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            capacity,
+            period: Duration::from_secs(secs),
+        })
+    }
+
+    // 초당 다시 차는 개수
+    fn refill_rate(&self) -> f64 {
+        self.capacity as f64 / self.period.as_secs_f64()
+    }
+}
+
+// ========================================
+// 버킷 저장소
+// ========================================
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset_secs: u64,
+    pub retry_after_secs: u64,
+}
+
+impl Decision {
+    fn apply_headers(&self, headers: &mut HeaderMap) {
+        headers.insert("ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("ratelimit-remaining", HeaderValue::from(self.remaining));
+        headers.insert("ratelimit-reset", HeaderValue::from(self.reset_secs));
+    }
+}
+
+pub struct RateLimiter {
+    quota: Quota,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(quota: Quota) -> Self {
+        Self {
+            quota,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn check(&self, key: &str) -> Decision {
+        self.check_at(key, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Decision {
+        let capacity = self.quota.capacity as f64;
+        let rate = self.quota.refill_rate();
+        let refilled = |bucket: &Bucket| {
+            let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+            (bucket.tokens + elapsed * rate).min(capacity)
+        };
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= MAX_TRACKED_KEYS {
+            buckets.retain(|_, bucket| refilled(bucket) < capacity);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+        bucket.tokens = refilled(bucket);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        Decision {
+            allowed,
+            limit: self.quota.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs: ((capacity - bucket.tokens) / rate).ceil() as u64,
+            retry_after_secs: if allowed {
+                0
+            } else {
+                ((1.0 - bucket.tokens) / rate).ceil().max(1.0) as u64
+            },
+        }
+    }
+}
+
+// ========================================
+// 미들웨어
+// ========================================
+
+// 인증 미들웨어가 요청에 넣어 두면 IP 대신 이 값으로 제한 (예: "user:alice")
+#[derive(Clone)]
+pub struct RateLimitKey(pub String);
+
+// 인증된 요청은 사용자, 아니면 클라이언트 IP
+// (IP는 into_make_service_with_connect_info로 실행해야 있음 → common/server.rs)
+fn client_key(req: &Request) -> String {
+    if let Some(RateLimitKey(key)) = req.extensions().get::<RateLimitKey>() {
+        return key.clone();
+    }
+
+    match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+// 라우트 그룹마다 다른 할당량:
+//   .route_layer(middleware::from_fn_with_state(limiter, rate_limit))
+pub async fn rate_limit(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let decision = limiter.check(&client_key(&req));
+
+    let mut response = if decision.allowed {
+        next.run(req).await
+    } else {
+        ApiError::TooManyRequests("rate_limited", decision.retry_after_secs).into_response()
+    };
+
+    // 리미터가 겹치면(IP별 바깥 + 사용자별 안쪽) 안쪽 리미터의 값을 그대로 둠
+    if !response.headers().contains_key("ratelimit-limit") {
+        decision.apply_headers(response.headers_mut());
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quota() {
+        assert_eq!(
+            Quota::parse("5/60"),
+            Ok(Quota {
+                capacity: 5,
+                period: Duration::from_secs(60)
+            })
+        );
+        assert!(Quota::parse("5").is_err());
+        assert!(Quota::parse("0/60").is_err());
+        assert!(Quota::parse("five/60").is_err());
+    }
+
+    #[test]
+    fn bucket_empties_and_refills() {
+        let limiter = RateLimiter::new(Quota::parse("2/10").unwrap());
+        let start = Instant::now();
+
+        assert!(limiter.check_at("a", start).allowed);
+        assert_eq!(limiter.check_at("a", start).remaining, 0);
+
+        let limited = limiter.check_at("a", start);
+        assert!(!limited.allowed);
+        assert_eq!(limited.retry_after_secs, 5);
+        assert_eq!(limited.reset_secs, 10);
+
+        // 다른 키는 따로 계산
+        assert!(limiter.check_at("b", start).allowed);
+
+        // 5초에 1개씩 다시 참
+        assert!(
+            limiter
+                .check_at("a", start + Duration::from_secs(5))
+                .allowed
+        );
+        assert!(
+            !limiter
+                .check_at("a", start + Duration::from_secs(5))
+                .allowed
+        );
+    }
+}
@@ -0,0 +1,100 @@
+// 요청 ID: 클라이언트가 보낸 X-Request-Id를 쓰거나 새로 생성
+//
+// 핸들러/에러에서 요청 객체 없이 꺼낼 수 있도록 task-local에 저장
+// (axum은 한 요청의 미들웨어 → 핸들러를 같은 task에서 실행)
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+pub static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+// 현재 처리 중인 요청의 ID (미들웨어 밖이면 None)
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+// 너무 길거나 이상한 값은 받지 않고 새로 생성
+fn incoming_id(req: &Request) -> Option<String> {
+    let value = req.headers().get(&X_REQUEST_ID)?.to_str().ok()?;
+    let valid = !value.is_empty()
+        && value.len() <= 128
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    valid.then(|| value.to_string())
+}
+
+// Router의 가장 바깥 레이어로 등록
+pub async fn request_id_middleware(req: Request, next: Next) -> Response {
+    let id = incoming_id(&req).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+
+    // 응답 헤더로 돌려줘서 클라이언트 로그와 맞춰볼 수 있게 함
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(X_REQUEST_ID.clone(), value);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn request(id: Option<&str>) -> Request {
+        let mut request = Request::builder().uri("/");
+        if let Some(id) = id {
+            request = request.header(&X_REQUEST_ID, id);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn incoming_id_accepts_only_short_safe_values() {
+        assert_eq!(
+            incoming_id(&request(Some("abc-123_x.y"))).as_deref(),
+            Some("abc-123_x.y")
+        );
+        assert_eq!(incoming_id(&request(None)), None);
+        assert_eq!(incoming_id(&request(Some(""))), None);
+        assert_eq!(incoming_id(&request(Some("has space"))), None);
+        assert_eq!(incoming_id(&request(Some(&"a".repeat(129)))), None);
+    }
+
+    #[tokio::test]
+    async fn middleware_echoes_or_generates_request_id() {
+        // 핸들러 안에서도 current()로 같은 ID를 꺼낼 수 있음
+        let app = Router::new()
+            .route("/", get(|| async { current().unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let response = app
+            .clone()
+            .oneshot(request(Some("client-42")))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[&X_REQUEST_ID], "client-42");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "client-42");
+
+        let response = app.oneshot(request(Some("bad id"))).await.unwrap();
+        let generated = response.headers()[&X_REQUEST_ID].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok());
+
+        // 요청 밖에서는 ID가 없음
+        assert_eq!(current(), None);
+    }
+}
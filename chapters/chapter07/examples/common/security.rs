@@ -0,0 +1,219 @@
+// CORS 정책과 보안 응답 헤더
+//
+// CORS: 다른 origin(예: chapter08 WASM 프런트엔드)의 브라우저 요청 허용 범위
+//   CORS_ALLOWED_ORIGINS=http://localhost:8080,https://app.example.com  (또는 *)
+//   CORS_ALLOWED_METHODS=GET,POST,PUT,PATCH,DELETE
+//   CORS_ALLOW_CREDENTIALS=false   (쿠키/인증 헤더를 브라우저가 함께 보내도 되는지)
+//   CORS_MAX_AGE_SECS=600          (preflight 결과 캐시 시간)
+//
+// 보안 헤더: 핸들러가 이미 넣은 값은 덮어쓰지 않음
+
+use axum::{
+    http::{header, HeaderName, HeaderValue, Method},
+    Router,
+};
+use std::str::FromStr;
+use std::time::Duration;
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    set_header::SetResponseHeaderLayer,
+};
+
+const DEFAULT_ORIGINS: &str = "http://localhost:8080,http://127.0.0.1:8080";
+const DEFAULT_METHODS: &str = "GET,POST,PUT,PATCH,DELETE";
+
+// ========================================
+// CORS
+// ========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllowedOrigins {
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    pub origins: AllowedOrigins,
+    pub methods: Vec<Method>,
+    pub allow_credentials: bool,
+    pub max_age: Duration,
+}
+
+// 환경 변수 없이 기본값만 (테스트용)
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self::parse(|_| None).expect("default CORS config is valid")
+    }
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Result<Self, String> {
+        Self::parse(|name| std::env::var(name).ok())
+    }
+
+    pub fn parse(env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let list = |name: &str, default: &str| -> Vec<String> {
+            env(name)
+                .unwrap_or_else(|| default.to_string())
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect()
+        };
+
+        let origins = list("CORS_ALLOWED_ORIGINS", DEFAULT_ORIGINS);
+        let origins = if origins.iter().any(|o| o == "*") {
+            AllowedOrigins::Any
+        } else {
+            AllowedOrigins::List(
+                origins
+                    .iter()
+                    .map(|o| {
+                        HeaderValue::from_str(o).map_err(|_| format!("Invalid CORS origin: {}", o))
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+
+        let methods = list("CORS_ALLOWED_METHODS", DEFAULT_METHODS)
+            .iter()
+            .map(|m| {
+                Method::from_str(&m.to_uppercase())
+                    .map_err(|_| format!("Invalid CORS method: {}", m))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let allow_credentials = match env("CORS_ALLOW_CREDENTIALS").as_deref() {
+            None | Some("false") => false,
+            Some("true") => true,
+            Some(v) => return Err(format!("Invalid CORS_ALLOW_CREDENTIALS: {}", v)),
+        };
+
+        // 브라우저는 credentials + "*" 조합을 거부 → 시작할 때 막음
+        if allow_credentials && origins == AllowedOrigins::Any {
+            return Err("CORS_ALLOW_CREDENTIALS=true requires explicit origins, not *".to_string());
+        }
+
+        let max_age = match env("CORS_MAX_AGE_SECS") {
+            Some(v) => v
+                .parse()
+                .map(Duration::from_secs)
+                .map_err(|_| format!("Invalid CORS_MAX_AGE_SECS: {}", v))?,
+            None => Duration::from_secs(600),
+        };
+
+        Ok(Self {
+            origins,
+            methods,
+            allow_credentials,
+            max_age,
+        })
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let origin = match &self.origins {
+            AllowedOrigins::Any => AllowOrigin::any(),
+            AllowedOrigins::List(list) => AllowOrigin::list(list.clone()),
+        };
+
+        CorsLayer::new()
+            .allow_origin(origin)
+            .allow_methods(self.methods.clone())
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                header::IF_MATCH,
+                header::IF_NONE_MATCH,
+                HeaderName::from_static("x-request-id"),
+            ])
+            // 브라우저 코드에서 읽을 수 있는 응답 헤더
+            .expose_headers([
+                HeaderName::from_static("x-request-id"),
+                header::ETAG,
+                header::RETRY_AFTER,
+                HeaderName::from_static("ratelimit-limit"),
+                HeaderName::from_static("ratelimit-remaining"),
+                HeaderName::from_static("ratelimit-reset"),
+            ])
+            .allow_credentials(self.allow_credentials)
+            .max_age(self.max_age)
+    }
+}
+
+// ========================================
+// 보안 헤더
+// ========================================
+
+// 모든 응답(문서, 404/405 포함)에 붙일 기본값
+pub fn security_headers<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let headers = [
+        (
+            header::STRICT_TRANSPORT_SECURITY,
+            "max-age=31536000; includeSubDomains",
+        ),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        (header::X_FRAME_OPTIONS, "DENY"),
+        (header::REFERRER_POLICY, "no-referrer"),
+    ];
+
+    headers.into_iter().fold(router, |router, (name, value)| {
+        router.layer(SetResponseHeaderLayer::if_not_present(
+            name,
+            HeaderValue::from_static(value),
+        ))
+    })
+}
+
+// JSON API 기준 CSP (HTML을 내보내지 않으므로 모두 차단)
+// layer는 이미 등록된 라우트에만 적용 → Swagger UI 같은 HTML은 나중에 merge
+pub fn strict_csp<S>(router: Router<S>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(SetResponseHeaderLayer::if_not_present(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'; frame-ancestors 'none'"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(env: &[(&str, &str)]) -> Result<CorsConfig, String> {
+        CorsConfig::parse(|name| {
+            env.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn defaults_allow_local_frontend() {
+        let config = CorsConfig::default();
+        assert_eq!(
+            config.origins,
+            AllowedOrigins::List(vec![
+                HeaderValue::from_static("http://localhost:8080"),
+                HeaderValue::from_static("http://127.0.0.1:8080"),
+            ])
+        );
+        assert!(!config.allow_credentials);
+        assert!(config.methods.contains(&Method::PATCH));
+    }
+
+    #[test]
+    fn rejects_credentials_with_any_origin() {
+        assert!(parse(&[("CORS_ALLOWED_ORIGINS", "*")]).is_ok());
+        assert!(parse(&[
+            ("CORS_ALLOWED_ORIGINS", "*"),
+            ("CORS_ALLOW_CREDENTIALS", "true")
+        ])
+        .is_err());
+        assert!(parse(&[("CORS_ALLOW_CREDENTIALS", "yes")]).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+// 서버 실행: 주소 설정, 바인드 에러 처리, 안전한 종료(graceful shutdown)
+//
+// 주소는 CLI 플래그 > 환경 변수 > 기본값 순서로 결정
+//   --host 127.0.0.1 --port 8080   (또는 --port=8080)
+//   HOST / PORT / SHUTDOWN_TIMEOUT_SECS
+//
+// Ctrl-C(SIGINT)나 SIGTERM을 받으면 새 연결은 받지 않고,
+// 처리 중인 요청이 끝날 때까지 최대 SHUTDOWN_TIMEOUT_SECS 동안 기다림
+// SSE처럼 끝나지 않는 응답은 Extension<Shutdown>으로 종료 시점을 알 수 있음
+
+use axum::{
+    extract::{Request, State},
+    middleware::{self, Next},
+    response::Response,
+    Extension, Router,
+};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+
+const DEFAULT_HOST: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 3000;
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ========================================
+// 에러
+// ========================================
+
+#[derive(Debug)]
+pub enum ServerError {
+    Config(String),
+    Bind {
+        addr: String,
+        source: std::io::Error,
+    },
+    Serve(std::io::Error),
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerError::Config(msg) => write!(f, "invalid server configuration: {}", msg),
+            ServerError::Bind { addr, source } => write!(f, "failed to bind {}: {}", addr, source),
+            ServerError::Serve(err) => write!(f, "server error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ServerError {}
+
+// ========================================
+// 설정
+// ========================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub shutdown_timeout: Duration,
+}
+
+impl ServerConfig {
+    pub fn from_env() -> Result<Self, ServerError> {
+        Self::parse(std::env::args().skip(1), |name| std::env::var(name).ok())
+    }
+
+    // 모르는 인자는 무시 (예제마다 자기 서브커맨드가 있을 수 있음)
+    pub fn parse(
+        args: impl IntoIterator<Item = String>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ServerError> {
+        let mut host = env("HOST");
+        let mut port = env("PORT");
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let slot = match flag.as_str() {
+                "--host" => &mut host,
+                "--port" => &mut port,
+                _ => continue,
+            };
+            let value = inline
+                .or_else(|| args.next())
+                .ok_or_else(|| ServerError::Config(format!("{} needs a value", flag)))?;
+            *slot = Some(value);
+        }
+
+        let port = match port {
+            Some(v) => v
+                .parse()
+                .map_err(|_| ServerError::Config(format!("invalid port: {}", v)))?,
+            None => DEFAULT_PORT,
+        };
+
+        let shutdown_timeout = match env("SHUTDOWN_TIMEOUT_SECS") {
+            Some(v) => v.parse().map(Duration::from_secs).map_err(|_| {
+                ServerError::Config(format!("invalid SHUTDOWN_TIMEOUT_SECS: {}", v))
+            })?,
+            None => DEFAULT_SHUTDOWN_TIMEOUT,
+        };
+
+        Ok(Self {
+            host: host.unwrap_or_else(|| DEFAULT_HOST.to_string()),
+            port,
+            shutdown_timeout,
+        })
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+// ========================================
+// 처리 중인 요청 수
+// ========================================
+
+#[derive(Clone, Default)]
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// 핸들러가 패닉하거나 연결이 끊겨도 Drop에서 감소
+struct InFlightGuard(InFlight);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+async fn track_in_flight(State(in_flight): State<InFlight>, req: Request, next: Next) -> Response {
+    in_flight.0.fetch_add(1, Ordering::SeqCst);
+    let _guard = InFlightGuard(in_flight);
+    next.run(req).await
+}
+
+// ========================================
+// 종료 알림
+// ========================================
+
+// 핸들러에서 Option<Extension<Shutdown>>으로 받아서 스트림을 끝낼 때 사용
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+impl Shutdown {
+    // 종료 신호를 받으면 완료 (서버가 먼저 사라져도 완료)
+    pub async fn wait(&self) {
+        let mut rx = self.0.clone();
+        let _ = rx.wait_for(|stopping| *stopping).await;
+    }
+}
+
+// ========================================
+// 실행
+// ========================================
+
+// 바인드 → 서빙 → 종료 신호 → 남은 요청 처리(제한 시간까지)
+pub async fn serve(app: Router, config: &ServerConfig) -> Result<(), ServerError> {
+    let addr = config.addr();
+    let listener = TcpListener::bind(&addr)
+        .await
+        .map_err(|source| ServerError::Bind {
+            addr: addr.clone(),
+            source,
+        })?;
+
+    println!("Listening on http://{}", addr);
+
+    let in_flight = InFlight::default();
+    let (stopping_tx, stopping_rx) = watch::channel(false);
+    let app = app
+        .layer(middleware::from_fn_with_state(
+            in_flight.clone(),
+            track_in_flight,
+        ))
+        .layer(Extension(Shutdown(stopping_rx)));
+
+    // 종료 신호를 받은 시점부터 제한 시간을 셈
+    let (signaled_tx, signaled_rx) = tokio::sync::oneshot::channel();
+    let shutdown = {
+        let in_flight = in_flight.clone();
+        async move {
+            shutdown_signal().await;
+            println!("Shutting down, {} request(s) in flight", in_flight.count());
+            let _ = stopping_tx.send(true);
+            let _ = signaled_tx.send(());
+        }
+    };
+    let deadline = async {
+        if signaled_rx.await.is_err() {
+            // 신호 없이 서버가 끝난 경우
+            std::future::pending::<()>().await;
+        }
+        tokio::time::sleep(config.shutdown_timeout).await;
+    };
+
+    // 요청 제한 등에서 클라이언트 IP(ConnectInfo)를 쓸 수 있게 함
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    let server = axum::serve(listener, service).with_graceful_shutdown(shutdown);
+
+    tokio::select! {
+        result = server => result.map_err(ServerError::Serve)?,
+        _ = deadline => {
+            eprintln!(
+                "Shutdown timed out after {:?}, dropping {} request(s)",
+                config.shutdown_timeout,
+                in_flight.count()
+            );
+        }
+    }
+
+    println!("Server stopped");
+    Ok(())
+}
+
+// Ctrl-C 또는 SIGTERM (컨테이너/systemd가 보내는 종료 신호)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                eprintln!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str], env: &[(&str, &str)]) -> Result<ServerConfig, ServerError> {
+        let env: Vec<(String, String)> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ServerConfig::parse(args.iter().map(|a| a.to_string()), |name| {
+            env.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+        })
+    }
+
+    #[test]
+    fn defaults_to_all_interfaces_on_3000() {
+        let config = parse(&[], &[]).unwrap();
+        assert_eq!(config.addr(), "0.0.0.0:3000");
+        assert_eq!(config.shutdown_timeout, DEFAULT_SHUTDOWN_TIMEOUT);
+    }
+
+    #[test]
+    fn flags_override_env() {
+        let env = [("HOST", "10.0.0.1"), ("PORT", "9000")];
+
+        assert_eq!(parse(&[], &env).unwrap().addr(), "10.0.0.1:9000");
+        assert_eq!(
+            parse(&["--port", "8080", "--host=127.0.0.1"], &env)
+                .unwrap()
+                .addr(),
+            "127.0.0.1:8080"
+        );
+    }
+
+    #[test]
+    fn ignores_other_arguments() {
+        let config = parse(&["migrate-down", "1", "--port", "4000"], &[]).unwrap();
+        assert_eq!(config.port, 4000);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        assert!(matches!(
+            parse(&["--port", "http"], &[]),
+            Err(ServerError::Config(_))
+        ));
+        assert!(matches!(
+            parse(&["--port"], &[]),
+            Err(ServerError::Config(_))
+        ));
+        assert!(matches!(
+            parse(&[], &[("SHUTDOWN_TIMEOUT_SECS", "soon")]),
+            Err(ServerError::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn bind_failure_is_an_error() {
+        let taken = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: taken.local_addr().unwrap().port(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        };
+
+        let result = serve(Router::new(), &config).await;
+        assert!(matches!(result, Err(ServerError::Bind { .. })));
+    }
+}
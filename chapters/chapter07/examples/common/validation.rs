@@ -0,0 +1,82 @@
+// 선언적 검증: 구조체 필드에 규칙을 적고 ValidatedJson으로 받으면
+// 규칙을 모두 검사한 뒤 실패한 필드를 한 번에 422로 응답
+//
+// #[derive(Deserialize, Validate)]
+// struct CreateUser {
+//     #[serde(deserialize_with = "trimmed")]
+//     #[validate(length(min = 1, max = 100))]
+//     name: String,
+// }
+//
+// async fn create_user(ValidatedJson(payload): ValidatedJson<CreateUser>) { ... }
+
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use validator::{Validate, ValidationErrors};
+
+use super::error::{ApiError, FieldError};
+use super::extract::Json;
+
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<S, T> FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        // JSON 형식/타입 에러는 Json 추출기에서 먼저 걸러짐
+        let Json(value) = Json::<T>::from_request(req, state).await?;
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
+
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        ApiError::Validation(field_errors(&errors))
+    }
+}
+
+// 여러 건을 한 번에 검사할 때(일괄 가져오기 등) 건마다 필드 에러 목록으로
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut fields: Vec<FieldError> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |err| {
+                let message = err
+                    .message
+                    .as_ref()
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| format!("{} is invalid", field));
+                FieldError::new(field, err.code.clone(), message)
+            })
+        })
+        .collect();
+
+    // HashMap 순서에 상관없이 항상 같은 순서로 응답
+    fields.sort_by(|a, b| a.field.cmp(&b.field));
+    fields
+}
+
+// 앞뒤 공백 제거 (검증 전에 적용)
+pub fn trimmed<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|s| s.trim().to_string())
+}
+
+pub fn trimmed_opt<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(|s| s.map(|s| s.trim().to_string()))
+}
@@ -1,124 +1,964 @@
 // STEP 7-7 ~ 7-9: 미들웨어와 인증
 // Cargo.toml:
 // [dependencies]
-// axum = "0.7"
+// axum = { version = "0.7", features = ["macros"] }
 // tokio = { version = "1", features = ["full"] }
 // serde = { version = "1", features = ["derive"] }
 // serde_json = "1"
-// tower-http = { version = "0.5", features = ["cors", "trace"] }
+// tower-http = { version = "0.5", features = ["cors", "set-header"] }
 // jsonwebtoken = "9"
 // chrono = "0.4"
+// argon2 = { version = "0.5", features = ["std"] }
+// uuid = { version = "1", features = ["v4"] }
+// ed25519-dalek = { version = "2", features = ["rand_core", "pem"] }
+// base64 = "0.22"
+// validator = { version = "0.18", features = ["derive"] }
+// tracing = "0.1"
+// tracing-subscriber = { version = "0.3", features = ["env-filter", "json"] }
+// prometheus = { version = "0.13", default-features = false }
+//
+// [dev-dependencies]
+// tower = { version = "0.4", features = ["util"] }
+// http-body-util = "0.1"
+//
+// 공통 코드: examples/common/ (에러 형식, 요청 ID, 로그, 메트릭, 요청 제한, CORS/보안 헤더, 검증, 서버 실행)
+// 로그: RUST_LOG=debug, LOG_FORMAT=json
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+mod common;
 
 use axum::{
-    extract::Request,
-    http::{header, StatusCode},
+    async_trait,
+    extract::{FromRequestParts, Request, State},
+    http::request::Parts,
+    http::{header, HeaderMap, StatusCode},
     middleware::{self, Next},
-    response::{IntoResponse, Response},
+    response::Response,
     routing::{get, post},
-    Json, Router,
+    Router,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use common::error::{self, ApiError, FieldError};
+use common::extract::Json;
+use common::logging;
+use common::metrics::{self, Metrics};
+use common::rate_limit::{rate_limit, Quota, RateLimitKey, RateLimiter};
+use common::request_id::request_id_middleware;
+use common::security::{self, CorsConfig};
+use common::server::{self, ServerConfig};
+use ed25519_dalek::{
+    pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey},
+    SigningKey, VerifyingKey,
+};
+use jsonwebtoken::{
+    decode, decode_header, encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    },
+    Algorithm, DecodingKey, EncodingKey, Header, Validation,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::time::Instant;
+use std::collections::HashMap;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
 
 // ========================================
-// 7-7. 로깅 미들웨어
+// 사용자 저장소 (Argon2id 비밀번호 해시)
 // ========================================
 
-async fn logging_middleware(req: Request, next: Next) -> Response {
-    let method = req.method().clone();
-    let uri = req.uri().clone();
-    let start = Instant::now();
+struct UserRecord {
+    username: String,
+    password_hash: String, // 평문은 저장하지 않음 ($argon2id$v=19$... 형식)
+    role: String,
+}
+
+// 실제로는 DB 테이블
+struct UserStore {
+    users: RwLock<HashMap<String, UserRecord>>,
+}
+
+const MIN_PASSWORD_LEN: usize = 8;
+
+// Argon2::default() = Argon2id, 사용자마다 랜덤 salt
+fn hash_password(password: &str) -> Result<String, ApiError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| ApiError::Internal(format!("Password hashing failed: {}", e)))
+}
+
+// 해시 비교는 argon2 내부에서 상수 시간(constant-time)으로 수행
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+fn validate_credentials(username: &str, password: &str) -> Result<(), ApiError> {
+    let mut errors = Vec::new();
+
+    let valid_username = (3..=32).contains(&username.len())
+        && username
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_username {
+        errors.push(FieldError::new(
+            "username",
+            "invalid_format",
+            "Username must be 3-32 characters of letters, digits, '_' or '-'",
+        ));
+    }
+
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        errors.push(FieldError::new(
+            "password",
+            "too_short",
+            "Password must be at least 8 characters",
+        ));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::Validation(errors))
+    }
+}
+
+impl UserStore {
+    fn new() -> Self {
+        Self {
+            users: RwLock::new(HashMap::new()),
+        }
+    }
+
+    async fn register(&self, username: &str, password: &str, role: &str) -> Result<(), ApiError> {
+        validate_credentials(username, password)?;
+
+        // 해시는 CPU를 많이 쓰므로 블로킹 스레드에서 실행
+        let password = password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))??;
+
+        let mut users = self.users.write().await;
+        if users.contains_key(username) {
+            return Err(ApiError::Conflict(
+                "username_taken",
+                "Username already taken".to_string(),
+            ));
+        }
+
+        users.insert(
+            username.to_string(),
+            UserRecord {
+                username: username.to_string(),
+                password_hash,
+                role: role.to_string(),
+            },
+        );
+
+        Ok(())
+    }
+
+    // 성공하면 (username, role) 반환
+    async fn authenticate(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<(String, String), ApiError> {
+        let (stored_hash, user) = {
+            let users = self.users.read().await;
+            match users.get(username) {
+                Some(record) => (
+                    record.password_hash.clone(),
+                    Some((record.username.clone(), record.role.clone())),
+                ),
+                // 없는 사용자도 같은 비용으로 검증해서 응답 시간으로 존재 여부를 알 수 없게 함
+                None => (dummy_hash().to_string(), None),
+            }
+        };
+
+        let password = password.to_string();
+        let valid = tokio::task::spawn_blocking(move || verify_password(&password, &stored_hash))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+        match user {
+            Some(user) if valid => Ok(user),
+            _ => Err(ApiError::InvalidCredentials),
+        }
+    }
+
+    // (username, role) 목록
+    async fn list(&self) -> Vec<(String, String)> {
+        let users = self.users.read().await;
+        let mut list: Vec<_> = users
+            .values()
+            .map(|u| (u.username.clone(), u.role.clone()))
+            .collect();
+        list.sort();
+        list
+    }
+
+    async fn count(&self) -> usize {
+        self.users.read().await.len()
+    }
+
+    // 리프레시 시점의 최신 역할 조회 (삭제된 사용자면 None)
+    async fn role(&self, username: &str) -> Option<String> {
+        self.users
+            .read()
+            .await
+            .get(username)
+            .map(|u| u.role.clone())
+    }
 
-    println!("--> {} {}", method, uri);
+    async fn change_password(
+        &self,
+        username: &str,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), ApiError> {
+        self.authenticate(username, current_password).await?;
+        validate_credentials(username, new_password)?;
 
-    let response = next.run(req).await;
+        let new_password = new_password.to_string();
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&new_password))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))??;
 
-    let duration = start.elapsed();
-    println!("<-- {} {} {:?} [{:?}]", method, uri, response.status(), duration);
+        let mut users = self.users.write().await;
+        let record = users
+            .get_mut(username)
+            .ok_or(ApiError::InvalidCredentials)?;
+        record.password_hash = password_hash;
 
-    response
+        Ok(())
+    }
+}
+
+// 존재하지 않는 사용자 로그인 시 비교용 해시 (처음 한 번만 생성)
+fn dummy_hash() -> &'static str {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    DUMMY_HASH.get_or_init(|| hash_password("dummy-password").unwrap_or_default())
+}
+
+struct AppState {
+    users: UserStore,
+    tokens: TokenStore,
+    keys: JwtKeys,
+    metrics: Metrics,
+    limits: RateLimits,
+    logins: LoginAttempts,
+    cors: CorsConfig,
+}
+
+impl AppState {
+    fn new(keys: JwtKeys, limits: RateLimitConfig, cors: CorsConfig) -> Self {
+        Self {
+            users: UserStore::new(),
+            tokens: TokenStore::new(keys.validation.leeway),
+            keys,
+            metrics: Metrics::new(),
+            limits: RateLimits {
+                auth: Arc::new(RateLimiter::new(limits.auth)),
+                public: Arc::new(RateLimiter::new(limits.public)),
+                api: Arc::new(RateLimiter::new(limits.api)),
+                api_ip: Arc::new(RateLimiter::new(limits.api_ip)),
+            },
+            logins: LoginAttempts::new(
+                limits.login_max_failures,
+                limits.login_window,
+                limits.login_lockout,
+            ),
+            cors,
+        }
+    }
 }
 
+type SharedState = Arc<AppState>;
+
 // ========================================
 // 7-9. JWT 인증
 // ========================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Claims {
     sub: String,        // subject (user id)
     exp: usize,         // expiration time
     iat: usize,         // issued at
+    iss: String,        // issuer (발급자)
+    aud: String,        // audience (대상 서비스)
     role: String,       // user role
+    jti: String,        // token id (폐기할 때 사용)
 }
 
-const JWT_SECRET: &[u8] = b"your-secret-key-here";
+const ACCESS_TOKEN_TTL: usize = 3600; // 1시간
+const REFRESH_TOKEN_TTL: usize = 14 * 24 * 3600; // 14일
 
-fn create_token(user_id: &str, role: &str) -> Result<String, jsonwebtoken::errors::Error> {
-    use jsonwebtoken::{encode, EncodingKey, Header};
+fn now() -> usize {
+    chrono::Utc::now().timestamp() as usize
+}
 
-    let now = chrono::Utc::now().timestamp() as usize;
-    let exp = now + 3600; // 1시간 후 만료
+// ========================================
+// JWT 서명 키 (EdDSA + 키 교체)
+// ========================================
+
+// 비밀 키는 바이너리에 넣지 않고 시작할 때 PEM 파일/환경 변수에서 읽음
+//   JWT_SIGNING_KEY_FILE=keys/2024-06.pem   (또는 JWT_SIGNING_KEY_PEM=<PEM 내용>)
+//   JWT_KEY_ID=2024-06                      (생략하면 파일 이름)
+//   JWT_PREVIOUS_KEYS=2024-01=keys/2024-01.pub.pem,...  (교체 전 공개 키, 검증만)
+//   JWT_ISSUER / JWT_AUDIENCE / JWT_LEEWAY_SECS
+//
+// 키 생성:
+//   openssl genpkey -algorithm ed25519 -out keys/2024-06.pem
+//   openssl pkey -in keys/2024-06.pem -pubout -out keys/2024-06.pub.pem
+struct JwtKeys {
+    active_kid: String,
+    encoding: EncodingKey,
+    decoding: HashMap<String, DecodingKey>, // kid -> 공개 키
+    jwks: JwkSet,
+    validation: Validation,
+    issuer: String,
+    audience: String,
+}
+
+impl JwtKeys {
+    fn from_env() -> Result<Self, String> {
+        let env = |name: &str| std::env::var(name).ok();
+
+        let issuer = env("JWT_ISSUER").unwrap_or_else(|| "learn-rust".to_string());
+        let audience = env("JWT_AUDIENCE").unwrap_or_else(|| "learn-rust-api".to_string());
+        let leeway = match env("JWT_LEEWAY_SECS") {
+            Some(v) => v
+                .parse()
+                .map_err(|_| format!("Invalid JWT_LEEWAY_SECS: {}", v))?,
+            None => 30,
+        };
+
+        let (active_kid, signing_key) =
+            match (env("JWT_SIGNING_KEY_PEM"), env("JWT_SIGNING_KEY_FILE")) {
+                (Some(pem), _) => (
+                    env("JWT_KEY_ID").ok_or("JWT_KEY_ID is required with JWT_SIGNING_KEY_PEM")?,
+                    SigningKey::from_pkcs8_pem(&pem)
+                        .map_err(|e| format!("Invalid signing key: {}", e))?,
+                ),
+                (None, Some(path)) => {
+                    let kid = env("JWT_KEY_ID").unwrap_or_else(|| key_id_from_path(&path));
+                    (
+                        kid,
+                        SigningKey::read_pkcs8_pem_file(&path)
+                            .map_err(|e| format!("{}: {}", path, e))?,
+                    )
+                }
+                (None, None) => {
+                    // 개발용: 매번 새 키 생성 (재시작하면 기존 토큰은 무효)
+                    tracing::warn!("JWT_SIGNING_KEY_FILE not set, using an ephemeral signing key");
+                    ("dev".to_string(), SigningKey::generate(&mut OsRng))
+                }
+            };
+
+        let mut public_keys = vec![(active_kid.clone(), signing_key.verifying_key())];
+        for entry in env("JWT_PREVIOUS_KEYS").iter().flat_map(|v| v.split(',')) {
+            let (kid, path) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("JWT_PREVIOUS_KEYS entry must be kid=path: {}", entry))?;
+            let key = VerifyingKey::read_public_key_pem_file(path.trim())
+                .map_err(|e| format!("{}: {}", path, e))?;
+            public_keys.push((kid.trim().to_string(), key));
+        }
+
+        let der = signing_key
+            .to_pkcs8_der()
+            .map_err(|e| format!("Invalid signing key: {}", e))?;
+
+        Ok(Self::new(
+            active_kid,
+            EncodingKey::from_ed_der(der.as_bytes()),
+            public_keys,
+            issuer,
+            audience,
+            leeway,
+        ))
+    }
+
+    fn new(
+        active_kid: String,
+        encoding: EncodingKey,
+        public_keys: Vec<(String, VerifyingKey)>,
+        issuer: String,
+        audience: String,
+        leeway: u64,
+    ) -> Self {
+        // 공개 키는 JWKS(JSON Web Key Set) 형식으로 보관하고 그대로 공개
+        let jwks = JwkSet {
+            keys: public_keys
+                .iter()
+                .map(|(kid, key)| Jwk {
+                    common: CommonParameters {
+                        public_key_use: Some(PublicKeyUse::Signature),
+                        key_algorithm: Some(KeyAlgorithm::EdDSA),
+                        key_id: Some(kid.clone()),
+                        ..Default::default()
+                    },
+                    algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x: URL_SAFE_NO_PAD.encode(key.as_bytes()),
+                    }),
+                })
+                .collect(),
+        };
+
+        let decoding = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                DecodingKey::from_jwk(jwk).ok().map(|key| (kid, key))
+            })
+            .collect();
+
+        let mut validation = Validation::new(Algorithm::EdDSA);
+        validation.set_issuer(&[&issuer]);
+        validation.set_audience(&[&audience]);
+        validation.leeway = leeway;
+
+        Self {
+            active_kid,
+            encoding,
+            decoding,
+            jwks,
+            validation,
+            issuer,
+            audience,
+        }
+    }
+}
+
+fn key_id_from_path(path: &str) -> String {
+    let name = std::path::Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path);
+    name.trim_end_matches(".pem")
+        .trim_end_matches(".pub")
+        .to_string()
+}
+
+struct AccessToken {
+    token: String,
+    jti: String,
+    exp: usize,
+}
+
+fn create_token(
+    keys: &JwtKeys,
+    user_id: &str,
+    role: &str,
+) -> Result<AccessToken, jsonwebtoken::errors::Error> {
+    let now = now();
+    let exp = now + ACCESS_TOKEN_TTL;
+    let jti = uuid::Uuid::new_v4().to_string();
 
     let claims = Claims {
         sub: user_id.to_string(),
         exp,
         iat: now,
+        iss: keys.issuer.clone(),
+        aud: keys.audience.clone(),
         role: role.to_string(),
+        jti: jti.clone(),
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
-    )
+    // 헤더의 kid로 검증할 때 어떤 키를 쓸지 알려줌
+    let mut header = Header::new(Algorithm::EdDSA);
+    header.kid = Some(keys.active_kid.clone());
+
+    let token = encode(&header, &claims, &keys.encoding)?;
+
+    Ok(AccessToken { token, jti, exp })
 }
 
-fn verify_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    use jsonwebtoken::{decode, DecodingKey, Validation};
+fn verify_token(keys: &JwtKeys, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    use jsonwebtoken::errors::ErrorKind;
 
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(JWT_SECRET),
-        &Validation::default(),
-    )
-    .map(|data| data.claims)
+    // 교체 기간에는 이전 키로 서명된 토큰도 kid로 찾아서 검증
+    let kid = decode_header(token)?.kid.ok_or(ErrorKind::InvalidToken)?;
+    let key = keys.decoding.get(&kid).ok_or(ErrorKind::InvalidKeyFormat)?;
+
+    decode::<Claims>(token, key, &keys.validation).map(|data| data.claims)
+}
+
+// ========================================
+// 리프레시 토큰과 폐기 목록
+// ========================================
+
+// 리프레시 토큰은 JWT가 아닌 랜덤 문자열 (서버에 저장된 것만 유효)
+struct RefreshRecord {
+    username: String,
+    family: String, // 로그인 한 번에서 이어지는 토큰 묶음
+    expires_at: usize,
+    used: bool,         // 이미 교체(rotation)된 토큰인지
+    access_jti: String, // 함께 발급된 액세스 토큰
+    access_exp: usize,
+}
+
+// 실제로는 Redis 같은 공유 저장소 (실무에서는 토큰 대신 해시를 키로 저장)
+struct TokenStore {
+    refresh: RwLock<HashMap<String, RefreshRecord>>,
+    revoked: RwLock<HashMap<String, usize>>, // jti -> 원래 만료 시각
+    leeway: usize,                           // 만료 후에도 검증을 통과하는 시간 (초)
+}
+
+enum RefreshOutcome {
+    Valid { username: String, family: String },
+    Reused,
+    Invalid,
+}
+
+impl TokenStore {
+    fn new(leeway: u64) -> Self {
+        Self {
+            refresh: RwLock::new(HashMap::new()),
+            revoked: RwLock::new(HashMap::new()),
+            leeway: leeway as usize,
+        }
+    }
+
+    async fn issue_refresh(&self, username: &str, family: &str, access: &AccessToken) -> String {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+
+        let mut refresh = self.refresh.write().await;
+        prune_refresh(&mut refresh, now(), self.leeway);
+        refresh.insert(
+            token.clone(),
+            RefreshRecord {
+                username: username.to_string(),
+                family: family.to_string(),
+                expires_at: now() + REFRESH_TOKEN_TTL,
+                used: false,
+                access_jti: access.jti.clone(),
+                access_exp: access.exp,
+            },
+        );
+
+        token
+    }
+
+    // 사용한 리프레시 토큰은 used로 표시 (한 번만 사용 가능)
+    async fn consume_refresh(&self, token: &str) -> RefreshOutcome {
+        let mut refresh = self.refresh.write().await;
+        prune_refresh(&mut refresh, now(), self.leeway);
+
+        let Some(record) = refresh.get_mut(token) else {
+            return RefreshOutcome::Invalid;
+        };
+
+        // 이미 교체된 토큰이 다시 오면 탈취된 것으로 간주
+        if record.used {
+            return RefreshOutcome::Reused;
+        }
+
+        record.used = true;
+        RefreshOutcome::Valid {
+            username: record.username.clone(),
+            family: record.family.clone(),
+        }
+    }
+
+    // 같은 묶음의 리프레시 토큰과 액세스 토큰을 모두 폐기
+    // owner가 있으면 그 사용자의 토큰일 때만 (다른 사용자의 토큰이면 false)
+    async fn revoke_family_of(&self, token: &str, owner: Option<&str>) -> bool {
+        let mut refresh = self.refresh.write().await;
+
+        let family = match refresh.get(token) {
+            Some(record) if owner.is_some_and(|owner| owner != record.username) => return false,
+            Some(record) => record.family.clone(),
+            None => return true,
+        };
+
+        let mut revoked = self.revoked.write().await;
+        refresh.retain(|_, record| {
+            if record.family != family {
+                return true;
+            }
+            revoked.insert(record.access_jti.clone(), record.access_exp);
+            false
+        });
+        true
+    }
+
+    // 비밀번호 변경 등: 그 사용자의 모든 묶음과 함께 발급된 액세스 토큰을 폐기
+    async fn revoke_user(&self, username: &str) {
+        let mut refresh = self.refresh.write().await;
+        let mut revoked = self.revoked.write().await;
+        refresh.retain(|_, record| {
+            if record.username != username {
+                return true;
+            }
+            revoked.insert(record.access_jti.clone(), record.access_exp);
+            false
+        });
+    }
+
+    async fn revoke_access(&self, jti: &str, exp: usize) {
+        let mut revoked = self.revoked.write().await;
+        // 만료 후 leeway까지 지난 항목은 검증에서 걸러지므로 목록에서 제거
+        let now = now();
+        revoked.retain(|_, exp| *exp + self.leeway > now);
+        revoked.insert(jti.to_string(), exp);
+    }
+
+    async fn is_revoked(&self, jti: &str) -> bool {
+        self.revoked.read().await.contains_key(jti)
+    }
+}
+
+// 로그인과 교체마다 기록이 생기므로 발급/사용할 때 정리
+//   - 만료된 리프레시 토큰
+//   - 사용한 토큰 중 짝인 액세스 토큰도 만료된 것 (leeway까지 지나서 폐기할 액세스 토큰이 없음)
+// 사용한 토큰은 그동안만 남아서 재사용을 감지 (그 뒤에 다시 오면 재사용 대신 그냥 무효)
+fn prune_refresh(refresh: &mut HashMap<String, RefreshRecord>, now: usize, leeway: usize) {
+    refresh.retain(|_, record| {
+        record.expires_at > now && !(record.used && record.access_exp + leeway <= now)
+    });
+}
+
+// Authorization: Bearer <token> 에서 토큰 부분만 추출
+fn bearer_token(headers: &HeaderMap) -> Result<&str, ApiError> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(ApiError::MissingToken)
 }
 
 // 인증 미들웨어
-async fn auth_middleware(req: Request, next: Next) -> Result<Response, AuthError> {
+async fn auth_middleware(
+    State(state): State<SharedState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
     // Authorization 헤더 확인
-    let auth_header = req
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|h| h.to_str().ok());
+    let token = bearer_token(req.headers())?;
 
-    let token = match auth_header {
-        Some(h) if h.starts_with("Bearer ") => &h[7..],
-        _ => return Err(AuthError::MissingToken),
-    };
+    // 토큰 검증 (서명, 만료) + 폐기 여부
+    let claims = verify_token(&state.keys, token).map_err(|_| ApiError::InvalidToken)?;
+    if state.tokens.is_revoked(&claims.jti).await {
+        return Err(ApiError::RevokedToken);
+    }
 
-    // 토큰 검증
-    match verify_token(token) {
-        Ok(_claims) => Ok(next.run(req).await),
-        Err(_) => Err(AuthError::InvalidToken),
+    // 이후 이 요청의 로그에는 user_id가 함께 남고, 요청 제한도 사용자별로 계산
+    logging::record_user(&claims.sub);
+    req.extensions_mut()
+        .insert(RateLimitKey(format!("user:{}", claims.sub)));
+
+    // 검증된 Claims를 요청에 담아 핸들러로 전달
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
+
+// 핸들러 인자로 `claims: Claims`를 쓰면 auth_middleware가 넣어둔 값을 꺼냄
+#[async_trait]
+impl<S> FromRequestParts<S> for Claims
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Claims>()
+            .cloned()
+            .ok_or(ApiError::MissingToken)
     }
 }
 
-// 인증 에러
-enum AuthError {
-    MissingToken,
-    InvalidToken,
+// 역할 검사 미들웨어: from_fn_with_state("admin", require_role)
+// 인증은 됐지만 권한이 없으면 401이 아닌 403
+async fn require_role(
+    State(role): State<&'static str>,
+    claims: Claims,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if claims.role != role {
+        return Err(ApiError::Forbidden);
+    }
+
+    Ok(next.run(req).await)
 }
 
-impl IntoResponse for AuthError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self {
-            AuthError::MissingToken => (StatusCode::UNAUTHORIZED, "Missing authorization token"),
-            AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token"),
-        };
+// 인증 에러는 ApiError의 MissingToken/InvalidToken/.../Forbidden 사용 (common/error.rs)
+
+// ========================================
+// 요청 제한과 로그인 잠금
+// ========================================
+
+// 라우트 그룹별 할당량 (환경 변수로 변경, 형식: <개수>/<초>)
+//   RATE_LIMIT_AUTH=10/60     /login, /register 등 (IP별)
+//   RATE_LIMIT_PUBLIC=60/60   나머지 공개 라우트 (IP별)
+//   RATE_LIMIT_API=120/60     /api/* (사용자별)
+//   RATE_LIMIT_API_IP=600/60  /api/* 인증 전 (IP별, 잘못된 토큰으로 서명 검증만 시키는 요청 제한)
+//   LOGIN_MAX_FAILURES=5, LOGIN_WINDOW_SECS=900, LOGIN_LOCKOUT_SECS=300
+#[derive(Debug, Clone)]
+struct RateLimitConfig {
+    auth: Quota,
+    public: Quota,
+    api: Quota,
+    api_ip: Quota,
+    login_max_failures: u32,
+    login_window: Duration,
+    login_lockout: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            auth: Quota::per_minute(10),
+            public: Quota::per_minute(60),
+            api: Quota::per_minute(120),
+            // NAT 뒤의 여러 사용자가 한 IP를 쓰므로 사용자별보다 넉넉하게
+            api_ip: Quota::per_minute(600),
+            login_max_failures: 5,
+            login_window: Duration::from_secs(900),
+            login_lockout: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn from_env() -> Result<Self, String> {
+        let mut config = Self::default();
+        let env = |name: &str| std::env::var(name).ok();
+
+        for (name, quota) in [
+            ("RATE_LIMIT_AUTH", &mut config.auth),
+            ("RATE_LIMIT_PUBLIC", &mut config.public),
+            ("RATE_LIMIT_API", &mut config.api),
+            ("RATE_LIMIT_API_IP", &mut config.api_ip),
+        ] {
+            if let Some(value) = env(name) {
+                *quota = Quota::parse(&value).map_err(|e| format!("{}: {}", name, e))?;
+            }
+        }
+        if let Some(value) = env("LOGIN_MAX_FAILURES") {
+            config.login_max_failures = value
+                .parse()
+                .map_err(|_| format!("Invalid LOGIN_MAX_FAILURES: {}", value))?;
+        }
+        for (name, duration) in [
+            ("LOGIN_WINDOW_SECS", &mut config.login_window),
+            ("LOGIN_LOCKOUT_SECS", &mut config.login_lockout),
+        ] {
+            if let Some(value) = env(name) {
+                *duration = value
+                    .parse()
+                    .map(Duration::from_secs)
+                    .map_err(|_| format!("Invalid {}: {}", name, value))?;
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+struct RateLimits {
+    auth: Arc<RateLimiter>,
+    public: Arc<RateLimiter>,
+    api: Arc<RateLimiter>,
+    api_ip: Arc<RateLimiter>,
+}
+
+// 사용자 이름별 로그인 실패 → 일정 시간 잠금
+// IP 제한만으로는 여러 IP에서 한 계정을 노리는 공격을 막지 못함
+// 없는 사용자 이름도 똑같이 잠가서 계정 존재 여부가 드러나지 않게 함
+// 실패 횟수는 첫 실패부터 window 동안만 셈 (가끔 하는 오타가 며칠에 걸쳐 쌓여 잠기지 않도록)
+// 진행 중인 시도도 check에서 같은 잠금 안에 미리 셈
+// → 비밀번호 확인(Argon2)이 끝나기 전에 동시에 보낸 요청들이 모두 통과하지 못하게
+struct LoginAttempts {
+    max_failures: u32,
+    window: Duration,
+    lockout: Duration,
+    failures: Mutex<HashMap<String, LoginFailures>>,
+}
+
+struct LoginFailures {
+    count: u32,
+    pending: u32, // check는 통과했지만 아직 결과가 기록되지 않은 시도
+    first: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl LoginFailures {
+    fn new(now: Instant) -> Self {
+        Self {
+            count: 0,
+            pending: 0,
+            first: now,
+            locked_until: None,
+        }
+    }
+
+    // 잠금이 없으면 셈 기간이 지난 실패를 버림
+    fn stale(&self, now: Instant, window: Duration) -> bool {
+        self.locked_until.is_none() && now.saturating_duration_since(self.first) >= window
+    }
+
+    // 진행 중인 시도가 없고, 잠금도 없고 남은 실패도 없으면 버려도 됨
+    fn expired(&self, now: Instant, window: Duration) -> bool {
+        if self.pending > 0 {
+            return false;
+        }
+        match self.locked_until {
+            Some(locked_until) => locked_until <= now,
+            None => self.count == 0 || self.stale(now, window),
+        }
+    }
+}
+
+// check가 예약한 시도: record로 결과를 남기거나, 그 전에 요청이 취소되면 예약만 돌려줌
+struct LoginAttempt<'a> {
+    logins: &'a LoginAttempts,
+    username: String,
+    recorded: bool,
+}
+
+impl LoginAttempt<'_> {
+    // 비밀번호가 틀리면 실패 횟수 증가, 맞으면 초기화
+    fn record<T>(mut self, result: &Result<T, ApiError>) {
+        self.recorded = true;
+        self.logins.record(&self.username, result);
+    }
+}
 
-        (status, Json(json!({ "error": message }))).into_response()
+impl Drop for LoginAttempt<'_> {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.logins.refund(&self.username);
+        }
+    }
+}
+
+// 아무 사용자 이름으로나 실패를 만들 수 있으므로 이만큼 쌓이면 끝난 기록을 정리
+// (rate_limit.rs의 MAX_TRACKED_KEYS와 같은 방식, /login의 IP 제한이 쌓이는 속도를 제한)
+const MAX_TRACKED_USERNAMES: usize = 10_000;
+
+impl LoginAttempts {
+    fn new(max_failures: u32, window: Duration, lockout: Duration) -> Self {
+        Self {
+            max_failures,
+            window,
+            lockout,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // 잠겨 있으면 비밀번호를 확인하지 않고 429
+    // 통과하면 시도 하나를 예약 (실패 횟수 + 진행 중인 시도가 max_failures를 넘지 않게)
+    fn check(&self, username: &str) -> Result<LoginAttempt<'_>, ApiError> {
+        self.check_at(username, Instant::now())
+    }
+
+    fn check_at(&self, username: &str, now: Instant) -> Result<LoginAttempt<'_>, ApiError> {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if failures.len() >= MAX_TRACKED_USERNAMES {
+            failures.retain(|_, entry| !entry.expired(now, self.window));
+        }
+
+        let entry = failures
+            .entry(username.to_string())
+            .or_insert_with(|| LoginFailures::new(now));
+        if let Some(locked_until) = entry.locked_until {
+            if locked_until > now {
+                let secs = (locked_until - now).as_secs_f64().ceil() as u64;
+                return Err(ApiError::TooManyRequests("login_locked", secs));
+            }
+            entry.locked_until = None;
+            entry.first = now;
+        }
+        if entry.stale(now, self.window) {
+            entry.count = 0;
+            entry.first = now;
+        }
+        // 남은 기회를 진행 중인 시도가 모두 쓰고 있음 → 결과가 나올 때까지 잠깐 기다리게
+        if entry.count + entry.pending >= self.max_failures {
+            return Err(ApiError::TooManyRequests("login_locked", 1));
+        }
+        entry.pending += 1;
+
+        Ok(LoginAttempt {
+            logins: self,
+            username: username.to_string(),
+            recorded: false,
+        })
+    }
+
+    fn record<T>(&self, username: &str, result: &Result<T, ApiError>) {
+        self.record_at(username, result, Instant::now())
+    }
+
+    fn record_at<T>(&self, username: &str, result: &Result<T, ApiError>, now: Instant) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = failures.get_mut(username) {
+            entry.pending = entry.pending.saturating_sub(1);
+        }
+        match result {
+            Ok(_) => {
+                failures.remove(username);
+            }
+            Err(ApiError::InvalidCredentials) => {
+                if failures.len() >= MAX_TRACKED_USERNAMES {
+                    failures.retain(|_, entry| !entry.expired(now, self.window));
+                }
+
+                let entry = failures
+                    .entry(username.to_string())
+                    .or_insert_with(|| LoginFailures::new(now));
+                if entry.stale(now, self.window) {
+                    entry.count = 0;
+                    entry.first = now;
+                }
+                entry.count += 1;
+                if entry.count >= self.max_failures {
+                    entry.count = 0;
+                    entry.locked_until = Some(now + self.lockout);
+                    tracing::warn!(username, "login locked after repeated failures");
+                }
+            }
+            Err(_) => {}
+        }
+        if failures
+            .get(username)
+            .is_some_and(|e| e.expired(now, self.window))
+        {
+            failures.remove(username);
+        }
+    }
+
+    // 결과 없이 끝난 시도 (요청 취소 등): 예약만 돌려줌
+    fn refund(&self, username: &str) {
+        let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = failures.get_mut(username) {
+            entry.pending = entry.pending.saturating_sub(1);
+            if entry.expired(Instant::now(), self.window) {
+                failures.remove(username);
+            }
+        }
     }
 }
 
@@ -126,6 +966,33 @@ impl IntoResponse for AuthError {
 // 핸들러
 // ========================================
 
+// 회원가입
+#[derive(Deserialize)]
+struct RegisterRequest {
+    username: String,
+    password: String,
+}
+
+async fn register(
+    State(state): State<SharedState>,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    // 가입 경로로는 항상 일반 사용자 권한
+    state
+        .users
+        .register(&payload.username, &payload.password, "user")
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "message": "User registered",
+            "username": payload.username,
+            "role": "user"
+        })),
+    ))
+}
+
 // 로그인 (토큰 발급)
 #[derive(Deserialize)]
 struct LoginRequest {
@@ -137,25 +1004,143 @@ struct LoginRequest {
 struct LoginResponse {
     token: String,
     token_type: String,
-    expires_in: u32,
+    expires_in: usize,
+    refresh_token: String,
+    refresh_expires_in: usize,
 }
 
-async fn login(Json(payload): Json<LoginRequest>) -> Result<Json<LoginResponse>, AuthError> {
-    // 실제로는 DB에서 사용자 확인
-    if payload.username == "admin" && payload.password == "password" {
-        let token = create_token(&payload.username, "admin")
-            .map_err(|_| AuthError::InvalidToken)?;
+// 액세스 토큰 + 리프레시 토큰 한 쌍 발급
+async fn issue_tokens(
+    state: &AppState,
+    username: &str,
+    role: &str,
+    family: &str,
+) -> Result<LoginResponse, ApiError> {
+    let access =
+        create_token(&state.keys, username, role).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let refresh_token = state.tokens.issue_refresh(username, family, &access).await;
 
-        Ok(Json(LoginResponse {
-            token,
-            token_type: "Bearer".to_string(),
-            expires_in: 3600,
-        }))
-    } else {
-        Err(AuthError::InvalidToken)
+    Ok(LoginResponse {
+        token: access.token,
+        token_type: "Bearer".to_string(),
+        expires_in: ACCESS_TOKEN_TTL,
+        refresh_token,
+        refresh_expires_in: REFRESH_TOKEN_TTL,
+    })
+}
+
+async fn login(
+    State(state): State<SharedState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    let attempt = state.logins.check(&payload.username)?;
+    let result = state
+        .users
+        .authenticate(&payload.username, &payload.password)
+        .await;
+    attempt.record(&result);
+    let (username, role) = result?;
+
+    // 저장된 사용자별 역할로 토큰 발급 (로그인마다 새 토큰 묶음)
+    let family = uuid::Uuid::new_v4().to_string();
+    Ok(Json(issue_tokens(&state, &username, &role, &family).await?))
+}
+
+// 토큰 갱신 (리프레시 토큰 교체)
+#[derive(Deserialize)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+async fn refresh_token(
+    State(state): State<SharedState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    match state.tokens.consume_refresh(&payload.refresh_token).await {
+        RefreshOutcome::Valid { username, family } => {
+            let role = state
+                .users
+                .role(&username)
+                .await
+                .ok_or(ApiError::InvalidToken)?;
+            Ok(Json(issue_tokens(&state, &username, &role, &family).await?))
+        }
+        RefreshOutcome::Reused => {
+            state
+                .tokens
+                .revoke_family_of(&payload.refresh_token, None)
+                .await;
+            Err(ApiError::RefreshTokenReused)
+        }
+        RefreshOutcome::Invalid => Err(ApiError::InvalidToken),
     }
 }
 
+// 로그아웃 (액세스 토큰 폐기, 리프레시 토큰을 보내면 그 묶음도 폐기)
+// 다른 사용자의 리프레시 토큰이면 403 (남의 로그인을 끊지 못하게)
+#[derive(Deserialize)]
+struct LogoutRequest {
+    refresh_token: Option<String>,
+}
+
+async fn logout(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    payload: Option<Json<LogoutRequest>>,
+) -> Result<StatusCode, ApiError> {
+    let token = bearer_token(&headers)?;
+    let claims = verify_token(&state.keys, token).map_err(|_| ApiError::InvalidToken)?;
+
+    if let Some(refresh_token) = payload.and_then(|Json(p)| p.refresh_token) {
+        if !state
+            .tokens
+            .revoke_family_of(&refresh_token, Some(&claims.sub))
+            .await
+        {
+            return Err(ApiError::Forbidden);
+        }
+    }
+    state.tokens.revoke_access(&claims.jti, claims.exp).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// 비밀번호 변경 (현재 비밀번호 확인 필요)
+#[derive(Deserialize)]
+struct ChangePasswordRequest {
+    username: String,
+    current_password: String,
+    new_password: String,
+}
+
+async fn change_password(
+    State(state): State<SharedState>,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    // 현재 비밀번호 확인도 로그인과 같은 실패 횟수에 포함
+    let attempt = state.logins.check(&payload.username)?;
+    let result = state
+        .users
+        .change_password(
+            &payload.username,
+            &payload.current_password,
+            &payload.new_password,
+        )
+        .await;
+    attempt.record(&result);
+    result?;
+
+    // 이전 비밀번호로 로그인한 세션(다른 기기 포함)은 모두 종료
+    state.tokens.revoke_user(&payload.username).await;
+
+    Ok(Json(json!({ "message": "Password changed" })))
+}
+
+// 공개 키 목록 (다른 서비스가 토큰을 직접 검증할 때 사용)
+async fn jwks(State(state): State<SharedState>) -> Json<JwkSet> {
+    Json(state.keys.jwks.clone())
+}
+
 // 공개 엔드포인트
 async fn public_route() -> Json<serde_json::Value> {
     Json(json!({
@@ -175,49 +1160,1063 @@ async fn protected_route() -> Json<serde_json::Value> {
     }))
 }
 
-async fn user_profile() -> Json<serde_json::Value> {
+// 토큰의 주인 정보 반환
+async fn user_profile(claims: Claims) -> Json<serde_json::Value> {
     Json(json!({
-        "id": 1,
-        "name": "Admin User",
-        "email": "admin@example.com",
-        "role": "admin"
+        "username": claims.sub,
+        "role": claims.role,
+        "token_expires_at": claims.exp
     }))
 }
 
+// Prometheus 수집 엔드포인트
+async fn metrics(State(state): State<SharedState>) -> Response {
+    state.metrics.set_users(state.users.count().await);
+    state.metrics.export()
+}
+
+// 관리자 전용: 가입된 계정 목록
+async fn list_accounts(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    let accounts: Vec<_> = state
+        .users
+        .list()
+        .await
+        .into_iter()
+        .map(|(username, role)| json!({ "username": username, "role": role }))
+        .collect();
+
+    Json(json!({ "accounts": accounts }))
+}
+
 // ========================================
 // 메인
 // ========================================
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// 시작 중 에러(키 파일, 포트 사용 중 등)는 패닉 대신 에러로 반환
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    logging::init();
+
+    let config = ServerConfig::from_env()?;
+    let keys = JwtKeys::from_env().map_err(|e| format!("Failed to load JWT keys: {}", e))?;
+    let limits = RateLimitConfig::from_env()?;
+    let cors = CorsConfig::from_env()?;
+
+    let state = Arc::new(AppState::new(keys, limits, cors));
+
+    // ADMIN_PASSWORD가 설정되어 있으면 관리자 계정 생성
+    if let Ok(password) = std::env::var("ADMIN_PASSWORD") {
+        if state
+            .users
+            .register("admin", &password, "admin")
+            .await
+            .is_err()
+        {
+            tracing::warn!("Failed to create admin account (password too short?)");
+        }
+    }
+
+    let app = app(state);
+
+    println!("Endpoints:");
+    println!("  GET  /          - Public route");
+    println!("  GET  /.well-known/jwks.json - JWT public keys");
+    println!("  POST /register  - Create account");
+    println!("  POST /login     - Get JWT + refresh token");
+    println!("  POST /token/refresh - Rotate refresh token");
+    println!("  POST /logout    - Revoke tokens");
+    println!("  POST /password  - Change password");
+    println!("  GET  /metrics   - Prometheus metrics");
+    println!("  GET  /api/protected - Protected route (requires token)");
+    println!("  GET  /api/profile   - User profile (requires token)");
+    println!("  GET  /api/admin/users - Account list (requires admin role)");
+    println!("\nTest register + login:");
+    println!(
+        "  curl -X POST http://localhost:{}/register \\",
+        config.port
+    );
+    println!("    -H 'Content-Type: application/json' \\");
+    println!("    -d '{{\"username\":\"alice\",\"password\":\"correct-horse\"}}'");
+    println!("  curl -X POST http://localhost:{}/login \\", config.port);
+    println!("    -H 'Content-Type: application/json' \\");
+    println!("    -d '{{\"username\":\"alice\",\"password\":\"correct-horse\"}}'");
+
+    server::serve(app, &config).await?;
+    Ok(())
+}
+
+// 라우터 설정 (테스트에서도 같은 라우터 사용)
+fn app(state: SharedState) -> Router {
+    // 인증 라우트 (비밀번호를 받는 곳이라 IP별로 엄격하게 제한)
+    let auth_routes = Router::new()
+        .route("/register", post(register))
+        .route("/login", post(login))
+        .route("/token/refresh", post(refresh_token))
+        .route("/logout", post(logout))
+        .route("/password", post(change_password))
+        .route_layer(middleware::from_fn_with_state(
+            state.limits.auth.clone(),
+            rate_limit,
+        ));
+
     // 공개 라우트
     let public_routes = Router::new()
         .route("/", get(public_route))
-        .route("/login", post(login));
+        .route("/.well-known/jwks.json", get(jwks))
+        .route("/metrics", get(metrics))
+        .route_layer(middleware::from_fn_with_state(
+            state.limits.public.clone(),
+            rate_limit,
+        ))
+        .merge(auth_routes)
+        .with_state(state.clone());
+
+    // 관리자 라우트 (admin 역할 필요)
+    let admin_routes = Router::new()
+        .route("/admin/users", get(list_accounts))
+        .route_layer(middleware::from_fn_with_state("admin", require_role))
+        .with_state(state.clone());
 
     // 보호된 라우트 (인증 필요)
     let protected_routes = Router::new()
         .route("/protected", get(protected_route))
         .route("/profile", get(user_profile))
-        .layer(middleware::from_fn(auth_middleware));
+        .merge(admin_routes)
+        // 사용자별 제한 (인증 미들웨어 안쪽이라 토큰의 sub 사용)
+        .route_layer(middleware::from_fn_with_state(
+            state.limits.api.clone(),
+            rate_limit,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+        // IP별 제한 (인증 미들웨어 바깥, 토큰이 없거나 잘못된 요청도 셈)
+        .layer(middleware::from_fn_with_state(
+            state.limits.api_ip.clone(),
+            rate_limit,
+        ));
 
     // 전체 앱
     let app = Router::new()
         .merge(public_routes)
         .nest("/api", protected_routes)
-        .layer(middleware::from_fn(logging_middleware));
+        .fallback(error::not_found)
+        .method_not_allowed_fallback(error::method_not_allowed)
+        // 라우트 템플릿별 요청 수/응답 시간
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            metrics::track_metrics,
+        ))
+        // 요청마다 tracing span (request_id 안쪽이어야 ID를 읽을 수 있음)
+        .layer(middleware::from_fn(logging::trace_request))
+        .layer(middleware::from_fn(request_id_middleware))
+        // 가장 바깥: preflight는 인증/요청 제한 전에 응답, 에러 응답에도 CORS 헤더
+        .layer(state.cors.layer());
 
-    println!("Server running at http://localhost:3000");
-    println!("\nEndpoints:");
-    println!("  GET  /          - Public route");
-    println!("  POST /login     - Get JWT token");
-    println!("  GET  /api/protected - Protected route (requires token)");
-    println!("  GET  /api/profile   - User profile (requires token)");
-    println!("\nTest login:");
-    println!("  curl -X POST http://localhost:3000/login \\");
-    println!("    -H 'Content-Type: application/json' \\");
-    println!("    -d '{{\"username\":\"admin\",\"password\":\"password\"}}'");
+    // 모든 응답에 보안 헤더 (HSTS, nosniff, frame/CSP)
+    security::security_headers(security::strict_csp(app))
+}
+
+// ========================================
+// 테스트
+// ========================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    const PASSWORD: &str = "correct-horse";
+
+    // 실행할 때마다 새 키로 서명하는 테스트용 키
+    fn test_keys() -> JwtKeys {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let der = signing_key.to_pkcs8_der().unwrap();
+        JwtKeys::new(
+            "test".to_string(),
+            EncodingKey::from_ed_der(der.as_bytes()),
+            vec![("test".to_string(), signing_key.verifying_key())],
+            "learn-rust".to_string(),
+            "learn-rust-api".to_string(),
+            0,
+        )
+    }
+
+    fn test_state() -> SharedState {
+        test_state_with(RateLimitConfig::default())
+    }
+
+    fn test_state_with(limits: RateLimitConfig) -> SharedState {
+        Arc::new(AppState::new(test_keys(), limits, CorsConfig::default()))
+    }
+
+    fn state_with_keys(keys: JwtKeys) -> SharedState {
+        Arc::new(AppState::new(
+            keys,
+            RateLimitConfig::default(),
+            CorsConfig::default(),
+        ))
+    }
+
+    async fn send(
+        state: &SharedState,
+        method: &str,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, HeaderMap, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let body = match body {
+            Some(json) => {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+            None => Body::empty(),
+        };
+
+        let response = app(state.clone())
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let json = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, headers, json)
+    }
+
+    // 가입 후 로그인해서 받은 응답 (token, refresh_token)
+    async fn register_and_login(state: &SharedState, username: &str) -> Value {
+        let credentials = json!({ "username": username, "password": PASSWORD });
+        let (status, _, _) =
+            send(state, "POST", "/register", None, Some(credentials.clone())).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, _, body) = send(state, "POST", "/login", None, Some(credentials)).await;
+        assert_eq!(status, StatusCode::OK);
+        body
+    }
+
+    #[tokio::test]
+    async fn public_route_needs_no_token() {
+        let state = test_state();
+        let (status, headers, body) = send(&state, "GET", "/", None, None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["auth_required"], false);
+        assert!(headers.contains_key("x-request-id"));
+    }
+
+    #[tokio::test]
+    async fn login_returns_token_for_protected_routes() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let token = login["token"].as_str().unwrap();
+
+        assert_eq!(login["token_type"], "Bearer");
+
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["username"], "alice");
+        assert_eq!(body["role"], "user");
+
+        let (status, _, _) = send(&state, "GET", "/api/protected", Some(token), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn login_with_wrong_password_is_unauthorized() {
+        let state = test_state();
+        register_and_login(&state, "alice").await;
+
+        let credentials = json!({ "username": "alice", "password": "wrong-password" });
+        let (status, _, body) = send(&state, "POST", "/login", None, Some(credentials)).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_credentials");
+    }
+
+    #[test]
+    fn passwords_are_stored_as_salted_argon2id_hashes() {
+        let first = hash_password(PASSWORD).unwrap();
+        let second = hash_password(PASSWORD).unwrap();
+
+        assert!(first.starts_with("$argon2id$"));
+        assert!(!first.contains(PASSWORD));
+        // 같은 비밀번호라도 salt가 달라서 해시가 다름
+        assert_ne!(first, second);
+
+        assert!(verify_password(PASSWORD, &first));
+        assert!(verify_password(PASSWORD, &second));
+        assert!(!verify_password("wrong-password", &first));
+        assert!(!verify_password(PASSWORD, "not-a-phc-string"));
+    }
+
+    #[tokio::test]
+    async fn login_for_unknown_user_looks_like_wrong_password() {
+        let state = test_state();
+
+        let credentials = json!({ "username": "nobody", "password": PASSWORD });
+        let (status, _, body) = send(&state, "POST", "/login", None, Some(credentials)).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_credentials");
+    }
+
+    #[tokio::test]
+    async fn change_password_replaces_the_old_password() {
+        let state = test_state();
+        let session = register_and_login(&state, "alice").await;
+        let login = |password: &str| json!({ "username": "alice", "password": password });
+
+        // 현재 비밀번호가 틀리면 바꾸지 않음
+        let change = json!({
+            "username": "alice",
+            "current_password": "wrong-password",
+            "new_password": "battery-staple"
+        });
+        let (status, _, body) = send(&state, "POST", "/password", None, Some(change)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_credentials");
+        let (status, _, _) = send(&state, "POST", "/login", None, Some(login(PASSWORD))).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 새 비밀번호도 가입할 때와 같은 규칙으로 검증
+        let change = json!({
+            "username": "alice",
+            "current_password": PASSWORD,
+            "new_password": "short"
+        });
+        let (status, _, body) = send(&state, "POST", "/password", None, Some(change)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "validation_failed");
+
+        let change = json!({
+            "username": "alice",
+            "current_password": PASSWORD,
+            "new_password": "battery-staple"
+        });
+        let (status, _, _) = send(&state, "POST", "/password", None, Some(change)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 바꾸기 전에 발급된 토큰은 더 이상 쓸 수 없음
+        let refresh = json!({ "refresh_token": session["refresh_token"] });
+        let (status, _, _) = send(&state, "POST", "/token/refresh", None, Some(refresh)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let token = session["token"].as_str().unwrap();
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some(token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "token_revoked");
+
+        let (status, _, _) = send(&state, "POST", "/login", None, Some(login(PASSWORD))).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _, body) = send(
+            &state,
+            "POST",
+            "/login",
+            None,
+            Some(login("battery-staple")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["token"].is_string());
+    }
+
+    #[tokio::test]
+    async fn register_rejects_duplicate_username() {
+        let state = test_state();
+        register_and_login(&state, "alice").await;
+
+        let credentials = json!({ "username": "alice", "password": PASSWORD });
+        let (status, _, _) = send(&state, "POST", "/register", None, Some(credentials)).await;
+
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_unauthorized() {
+        let state = test_state();
+        let (status, headers, body) = send(&state, "GET", "/api/protected", None, None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "missing_token");
+        assert!(headers.contains_key(header::WWW_AUTHENTICATE));
+    }
+
+    #[tokio::test]
+    async fn invalid_token_is_unauthorized() {
+        let state = test_state();
+        let (status, _, body) =
+            send(&state, "GET", "/api/protected", Some("not-a-jwt"), None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_token");
+    }
+
+    #[tokio::test]
+    async fn token_signed_by_another_key_is_unauthorized() {
+        let state = test_state();
+        let other = create_token(&test_keys(), "alice", "admin").unwrap();
+
+        let (status, _, body) =
+            send(&state, "GET", "/api/protected", Some(&other.token), None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_token");
+    }
+
+    // (이전 키, 새 키)
+    fn signing_keys() -> (SigningKey, SigningKey) {
+        (
+            SigningKey::generate(&mut OsRng),
+            SigningKey::generate(&mut OsRng),
+        )
+    }
+
+    // kid, 서명 키, 공개할 키 목록, 발급자/대상을 직접 정한 키 묶음
+    fn jwt_keys(
+        kid: &str,
+        signing_key: &SigningKey,
+        published: &[(&str, &SigningKey)],
+        issuer: &str,
+        audience: &str,
+    ) -> JwtKeys {
+        let der = signing_key.to_pkcs8_der().unwrap();
+        let public_keys = published
+            .iter()
+            .map(|(kid, key)| (kid.to_string(), key.verifying_key()))
+            .collect();
+        JwtKeys::new(
+            kid.to_string(),
+            EncodingKey::from_ed_der(der.as_bytes()),
+            public_keys,
+            issuer.to_string(),
+            audience.to_string(),
+            0,
+        )
+    }
+
+    // 키 교체 중인 서버: 새 키(2025)로 서명하고 이전 키(2024)도 검증용으로 공개
+    fn rotated_state(old_key: &SigningKey, new_key: &SigningKey) -> SharedState {
+        state_with_keys(jwt_keys(
+            "2025",
+            new_key,
+            &[("2025", new_key), ("2024", old_key)],
+            "learn-rust",
+            "learn-rust-api",
+        ))
+    }
+
+    #[tokio::test]
+    async fn token_signed_by_previous_key_still_verifies() {
+        let (old_key, new_key) = signing_keys();
+        let state = rotated_state(&old_key, &new_key);
+
+        // 교체 전에 이전 키로 발급된 토큰
+        let before = jwt_keys("2024", &old_key, &[], "learn-rust", "learn-rust-api");
+        let old = create_token(&before, "alice", "user").unwrap();
+        let (status, _, _) = send(&state, "GET", "/api/protected", Some(&old.token), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let new = create_token(&state.keys, "alice", "user").unwrap();
+        let (status, _, _) = send(&state, "GET", "/api/protected", Some(&new.token), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn token_with_unknown_kid_is_unauthorized() {
+        let (old_key, new_key) = signing_keys();
+        let state = rotated_state(&old_key, &new_key);
+
+        // 서명은 현재 키로 했지만 공개되지 않은 kid
+        let retired = jwt_keys("2023", &new_key, &[], "learn-rust", "learn-rust-api");
+        let token = create_token(&retired, "alice", "user").unwrap();
+        let (status, _, _) = send(&state, "GET", "/api/protected", Some(&token.token), None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn wrong_issuer_or_audience_is_unauthorized() {
+        let (_, key) = signing_keys();
+        let published = [("test", &key)];
+        let state = state_with_keys(jwt_keys(
+            "test",
+            &key,
+            &published,
+            "learn-rust",
+            "learn-rust-api",
+        ));
+
+        for (issuer, audience) in [
+            ("someone-else", "learn-rust-api"),
+            ("learn-rust", "other-api"),
+        ] {
+            let keys = jwt_keys("test", &key, &published, issuer, audience);
+            let token = create_token(&keys, "alice", "user").unwrap();
+            let (status, _, _) =
+                send(&state, "GET", "/api/protected", Some(&token.token), None).await;
+
+            assert_eq!(
+                status,
+                StatusCode::UNAUTHORIZED,
+                "iss={} aud={}",
+                issuer,
+                audience
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn jwks_publishes_every_key_with_its_kid() {
+        let (old_key, new_key) = signing_keys();
+        let state = rotated_state(&old_key, &new_key);
+
+        let (status, _, body) = send(&state, "GET", "/.well-known/jwks.json", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let keys = body["keys"].as_array().unwrap();
+        assert_eq!(keys.len(), 2);
+        for (jwk, (kid, key)) in keys.iter().zip([("2025", &new_key), ("2024", &old_key)]) {
+            assert_eq!(jwk["kid"], kid);
+            assert_eq!(jwk["kty"], "OKP");
+            assert_eq!(jwk["crv"], "Ed25519");
+            assert_eq!(jwk["alg"], "EdDSA");
+            assert_eq!(jwk["use"], "sig");
+            assert_eq!(
+                jwk["x"],
+                URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_unauthorized() {
+        let state = test_state();
+        let claims = Claims {
+            sub: "alice".to_string(),
+            exp: now() - 60,
+            iat: now() - 120,
+            iss: state.keys.issuer.clone(),
+            aud: state.keys.audience.clone(),
+            role: "user".to_string(),
+            jti: "expired".to_string(),
+        };
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(state.keys.active_kid.clone());
+        let token = encode(&header, &claims, &state.keys.encoding).unwrap();
+
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some(&token), None).await;
+
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "invalid_token");
+    }
+
+    #[tokio::test]
+    async fn logout_revokes_access_token() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let token = login["token"].as_str().unwrap();
+
+        let (status, _, _) = send(&state, "POST", "/logout", Some(token), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some(token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "token_revoked");
+    }
+
+    #[tokio::test]
+    async fn revoked_token_stays_revoked_within_leeway() {
+        let mut keys = test_keys();
+        keys.validation.leeway = 30;
+        let state = state_with_keys(keys);
+
+        // 만료됐지만 leeway 안이라서 아직 검증을 통과하는 토큰
+        let claims = Claims {
+            sub: "alice".to_string(),
+            exp: now() - 5,
+            iat: now() - 65,
+            iss: state.keys.issuer.clone(),
+            aud: state.keys.audience.clone(),
+            role: "user".to_string(),
+            jti: "almost-expired".to_string(),
+        };
+        let mut header = Header::new(Algorithm::EdDSA);
+        header.kid = Some(state.keys.active_kid.clone());
+        let token = encode(&header, &claims, &state.keys.encoding).unwrap();
+
+        let (status, _, _) = send(&state, "GET", "/api/profile", Some(&token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _, _) = send(&state, "POST", "/logout", Some(&token), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        // 다른 로그아웃이 폐기 목록을 정리해도 leeway가 끝날 때까지는 남아 있어야 함
+        let login = register_and_login(&state, "bob").await;
+        let other = login["token"].as_str().unwrap();
+        let (status, _, _) = send(&state, "POST", "/logout", Some(other), None).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some(&token), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "token_revoked");
+    }
+
+    #[tokio::test]
+    async fn logout_cannot_revoke_another_users_refresh_token() {
+        let state = test_state();
+        let alice = register_and_login(&state, "alice").await;
+        let bob = register_and_login(&state, "bob").await;
+
+        let stolen = json!({ "refresh_token": bob["refresh_token"] });
+        let (status, _, _) = send(
+            &state,
+            "POST",
+            "/logout",
+            alice["token"].as_str(),
+            Some(stolen.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        // bob의 토큰은 그대로 사용 가능
+        let (status, _, _) = send(&state, "POST", "/token/refresh", None, Some(stolen)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 자기 토큰이면 묶음 전체 폐기
+        let own = json!({ "refresh_token": alice["refresh_token"] });
+        let (status, _, _) = send(
+            &state,
+            "POST",
+            "/logout",
+            alice["token"].as_str(),
+            Some(own.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _, _) = send(&state, "POST", "/token/refresh", None, Some(own)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn reused_refresh_token_is_rejected() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let refresh = json!({ "refresh_token": login["refresh_token"] });
+
+        let (status, _, _) = send(
+            &state,
+            "POST",
+            "/token/refresh",
+            None,
+            Some(refresh.clone()),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _, body) = send(&state, "POST", "/token/refresh", None, Some(refresh)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["code"], "refresh_token_reused");
+    }
+
+    #[tokio::test]
+    async fn refresh_records_are_pruned() {
+        let store = TokenStore::new(0);
+        let access = |exp: usize| AccessToken {
+            token: String::new(),
+            jti: uuid::Uuid::new_v4().to_string(),
+            exp,
+        };
+        let expired = store.issue_refresh("alice", "a", &access(now() + 60)).await;
+        let used = store.issue_refresh("alice", "b", &access(now() - 1)).await;
+        let in_use = store.issue_refresh("alice", "c", &access(now() + 60)).await;
+        {
+            let mut refresh = store.refresh.write().await;
+            refresh.get_mut(&expired).unwrap().expires_at = now() - 1;
+            refresh.get_mut(&used).unwrap().used = true;
+            refresh.get_mut(&in_use).unwrap().used = true;
+        }
+
+        // 사용 중인 액세스 토큰의 짝은 재사용 감지를 위해 남음
+        store.issue_refresh("alice", "d", &access(now() + 60)).await;
+        let refresh = store.refresh.read().await;
+        assert_eq!(refresh.len(), 2);
+        assert!(refresh.contains_key(&in_use));
+    }
+
+    #[tokio::test]
+    async fn admin_routes_require_admin_role() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let token = login["token"].as_str().unwrap();
+
+        let (status, _, body) = send(&state, "GET", "/api/admin/users", Some(token), None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "forbidden");
+
+        state
+            .users
+            .register("root", PASSWORD, "admin")
+            .await
+            .unwrap();
+        let credentials = json!({ "username": "root", "password": PASSWORD });
+        let (_, _, login) = send(&state, "POST", "/login", None, Some(credentials)).await;
+        let admin_token = login["token"].as_str().unwrap();
+
+        let (status, _, body) =
+            send(&state, "GET", "/api/admin/users", Some(admin_token), None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["accounts"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn unknown_route_is_problem_json_404() {
+        let state = test_state();
+        let (status, headers, body) = send(&state, "GET", "/nope", None, None).await;
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "route_not_found");
+        assert_eq!(headers[header::CONTENT_TYPE], "application/problem+json");
+    }
+
+    // 로그를 메모리에 모으는 writer (JSON 한 줄씩)
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn request_logs_carry_request_id_and_user() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let token = login["token"].as_str().unwrap();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = Request::builder()
+            .uri("/api/profile")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header("x-request-id", "trace-me-123")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(state).oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["x-request-id"], "trace-me-123");
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let line: Value = output
+            .lines()
+            .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+            .find(|l| l["message"] == "request completed")
+            .expect("request log line");
+
+        assert_eq!(line["status"], 200);
+        assert!(line["latency_ms"].is_number());
+        assert_eq!(line["span"]["request_id"], "trace-me-123");
+        assert_eq!(line["span"]["path"], "/api/profile");
+        assert_eq!(line["span"]["user_id"], "alice");
+    }
+
+    #[tokio::test]
+    async fn metrics_use_route_templates() {
+        let state = test_state();
+        let login = register_and_login(&state, "alice").await;
+        let token = login["token"].as_str().unwrap();
+        send(&state, "GET", "/api/profile", Some(token), None).await;
+        send(&state, "GET", "/api/profile", None, None).await;
+
+        let response = app(state.clone())
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+        assert!(text
+            .contains(r#"http_requests_total{method="GET",route="/api/profile",status="200"} 1"#));
+        assert!(text
+            .contains(r#"http_requests_total{method="GET",route="/api/profile",status="401"} 1"#));
+        assert!(text.contains("http_request_duration_seconds_bucket"));
+        assert!(text.contains("app_users 1"));
+    }
+
+    #[tokio::test]
+    async fn auth_routes_are_rate_limited() {
+        let state = test_state_with(RateLimitConfig {
+            auth: Quota::parse("2/60").unwrap(),
+            ..RateLimitConfig::default()
+        });
+        let credentials = json!({ "username": "nobody", "password": PASSWORD });
+
+        for remaining in ["1", "0"] {
+            let (status, headers, _) =
+                send(&state, "POST", "/login", None, Some(credentials.clone())).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(headers["ratelimit-limit"], "2");
+            assert_eq!(headers["ratelimit-remaining"], remaining);
+        }
+
+        let (status, headers, body) = send(&state, "POST", "/login", None, Some(credentials)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "rate_limited");
+        // 60초에 2개 → 빈 버킷에 1개가 차기까지 최대 30초
+        let retry_after: u64 = headers[header::RETRY_AFTER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=30).contains(&retry_after));
+
+        // 공개 라우트는 별도 할당량
+        let (status, _, _) = send(&state, "GET", "/", None, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn api_routes_are_limited_per_user() {
+        let state = test_state_with(RateLimitConfig {
+            api: Quota::parse("1/60").unwrap(),
+            ..RateLimitConfig::default()
+        });
+        let alice = register_and_login(&state, "alice").await;
+        let bob = register_and_login(&state, "bob").await;
+        let alice = alice["token"].as_str().unwrap();
+        let bob = bob["token"].as_str().unwrap();
+
+        let (status, _, _) = send(&state, "GET", "/api/profile", Some(alice), None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _, _) = send(&state, "GET", "/api/profile", Some(alice), None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        // 같은 IP라도 다른 사용자는 영향 없음
+        let (status, _, _) = send(&state, "GET", "/api/profile", Some(bob), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // 인증에 실패하는 요청도 IP별로 제한 (서명 검증을 무제한으로 시키지 못하게)
+    #[tokio::test]
+    async fn unauthenticated_api_requests_are_limited_per_ip() {
+        let state = test_state_with(RateLimitConfig {
+            api_ip: Quota::parse("2/60").unwrap(),
+            ..RateLimitConfig::default()
+        });
+
+        for token in [None, Some("not-a-jwt")] {
+            let (status, headers, _) = send(&state, "GET", "/api/profile", token, None).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(headers["ratelimit-limit"], "2");
+        }
+        let (status, _, body) = send(&state, "GET", "/api/profile", Some("not-a-jwt"), None).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "rate_limited");
+    }
+
+    #[tokio::test]
+    async fn repeated_login_failures_lock_the_account() {
+        let state = test_state_with(RateLimitConfig {
+            login_max_failures: 2,
+            ..RateLimitConfig::default()
+        });
+        register_and_login(&state, "alice").await;
+
+        let wrong = json!({ "username": "alice", "password": "wrong-password" });
+        for _ in 0..2 {
+            let (status, _, _) = send(&state, "POST", "/login", None, Some(wrong.clone())).await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+        }
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+        // 잠긴 동안은 올바른 비밀번호도 거부
+        let right = json!({ "username": "alice", "password": PASSWORD });
+        let (status, headers, body) = send(&state, "POST", "/login", None, Some(right)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["code"], "login_locked");
+        assert!(headers.contains_key(header::RETRY_AFTER));
+    }
+
+    // 동시에 보낸 로그인도 실패 횟수를 넘어서 비밀번호를 확인하지 못함
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_logins_cannot_exceed_the_failure_limit() {
+        let state = test_state_with(RateLimitConfig {
+            auth: Quota::per_minute(100),
+            login_max_failures: 3,
+            ..RateLimitConfig::default()
+        });
+        register_and_login(&state, "alice").await;
+
+        let wrong = json!({ "username": "alice", "password": "wrong-password" });
+        let attempts: Vec<_> = (0..10)
+            .map(|_| {
+                let state = state.clone();
+                let wrong = wrong.clone();
+                tokio::spawn(async move { send(&state, "POST", "/login", None, Some(wrong)).await })
+            })
+            .collect();
+        let mut checked = 0;
+        for attempt in attempts {
+            let (status, _, body) = attempt.await.unwrap();
+            if status == StatusCode::UNAUTHORIZED {
+                checked += 1;
+            } else {
+                assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+                assert_eq!(body["code"], "login_locked");
+            }
+        }
+        assert_eq!(checked, 3);
+
+        // 세 번 모두 실패로 끝났으므로 잠금
+        let right = json!({ "username": "alice", "password": PASSWORD });
+        let (status, _, _) = send(&state, "POST", "/login", None, Some(right)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn reserved_login_attempt_is_refunded_when_dropped() {
+        let logins = LoginAttempts::new(1, Duration::from_secs(60), Duration::from_secs(300));
+        let now = Instant::now();
+
+        // 하나가 진행 중이면 다음 시도는 기다려야 함
+        let attempt = logins.check_at("alice", now).unwrap();
+        assert!(logins.check_at("alice", now).is_err());
+
+        // 결과 없이 끝나면(요청 취소) 예약만 돌려줌
+        drop(attempt);
+        assert!(logins.check_at("alice", now).is_ok());
+        assert!(logins.failures.lock().unwrap().is_empty());
+
+        // 성공하면 실패 기록도 지움
+        let attempt = logins.check_at("alice", now).unwrap();
+        attempt.record(&Ok::<(), ApiError>(()));
+        assert!(logins.failures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn login_failures_are_counted_within_a_window() {
+        let logins = LoginAttempts::new(3, Duration::from_secs(60), Duration::from_secs(300));
+        let failed: Result<(), ApiError> = Err(ApiError::InvalidCredentials);
+        let start = Instant::now();
+
+        // 셈 기간이 지난 실패는 버림 → 오타가 오래 쌓여도 잠기지 않음
+        logins.record_at("alice", &failed, start);
+        logins.record_at("alice", &failed, start + Duration::from_secs(30));
+        logins.record_at("alice", &failed, start + Duration::from_secs(61));
+        assert!(logins
+            .check_at("alice", start + Duration::from_secs(62))
+            .is_ok());
+
+        // 기간 안에 세 번 → 잠금, 잠금이 끝나면 기록도 삭제
+        let later = start + Duration::from_secs(70);
+        logins.record_at("alice", &failed, later);
+        logins.record_at("alice", &failed, later);
+        assert!(logins.check_at("alice", later).is_err());
+        assert!(logins
+            .check_at("alice", later + Duration::from_secs(301))
+            .is_ok());
+        assert!(logins.failures.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn login_failures_for_many_usernames_are_pruned() {
+        let logins = LoginAttempts::new(5, Duration::from_secs(60), Duration::from_secs(300));
+        let failed: Result<(), ApiError> = Err(ApiError::InvalidCredentials);
+        let start = Instant::now();
+
+        for n in 0..MAX_TRACKED_USERNAMES {
+            logins.record_at(&format!("user{}", n), &failed, start);
+        }
+        // 셈 기간이 지난 기록은 정리되고 새 기록만 남음
+        logins.record_at("late", &failed, start + Duration::from_secs(61));
+        assert_eq!(logins.failures.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_is_answered_before_auth() {
+        let state = test_state();
+        let preflight = |origin: &'static str| {
+            Request::builder()
+                .method("OPTIONS")
+                .uri("/api/profile")
+                .header(header::ORIGIN, origin)
+                .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+                .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app(state.clone())
+            .oneshot(preflight("http://localhost:8080"))
+            .await
+            .unwrap();
+        let headers = response.headers();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "http://localhost:8080"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap()
+            .contains("DELETE"));
+
+        // 허용하지 않은 origin에는 CORS 헤더를 주지 않음 (브라우저가 차단)
+        let response = app(state)
+            .oneshot(preflight("https://evil.example"))
+            .await
+            .unwrap();
+        assert!(!response
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn responses_carry_security_headers() {
+        let state = test_state();
+
+        // 성공 응답과 에러 응답 모두
+        for uri in ["/", "/api/profile", "/nope"] {
+            let (_, headers, _) = send(&state, "GET", uri, None, None).await;
+            assert_eq!(
+                headers[header::X_CONTENT_TYPE_OPTIONS],
+                "nosniff",
+                "{}",
+                uri
+            );
+            assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+            assert!(headers.contains_key(header::STRICT_TRANSPORT_SECURITY));
+            assert!(headers.contains_key(header::CONTENT_SECURITY_POLICY));
+        }
+    }
 }
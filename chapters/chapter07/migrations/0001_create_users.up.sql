@@ -0,0 +1,6 @@
+-- 사용자 테이블
+CREATE TABLE users (
+    id    INTEGER PRIMARY KEY AUTOINCREMENT,
+    name  TEXT    NOT NULL,
+    email TEXT    NOT NULL
+);
@@ -0,0 +1,2 @@
+-- 이메일 중복 방지 (대소문자 구분 없이)
+CREATE UNIQUE INDEX idx_users_email ON users (email COLLATE NOCASE);
@@ -0,0 +1,2 @@
+-- 낙관적 동시성 제어용 버전 (수정할 때마다 1 증가, ETag로 노출)
+ALTER TABLE users ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
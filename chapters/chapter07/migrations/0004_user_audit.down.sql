@@ -0,0 +1,7 @@
+-- 되돌린 뒤에는 삭제가 곧 실제 삭제이므로 소프트 삭제된 사용자는 지움 (되살리지 않음)
+DELETE FROM users WHERE deleted_at IS NOT NULL;
+DROP TRIGGER user_audit_no_delete;
+DROP TRIGGER user_audit_no_update;
+DROP INDEX idx_user_audit_user;
+DROP TABLE user_audit;
+ALTER TABLE users DROP COLUMN deleted_at;
@@ -0,0 +1,27 @@
+-- 소프트 삭제: 삭제 시각이 있으면 일반 조회에서 숨김 (퍼지 작업이 보존 기간 뒤 실제로 삭제)
+-- 삭제된 사용자의 이메일도 퍼지 전까지는 예약된 상태 (복원이 항상 가능하도록)
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
+
+-- 사용자 변경 이력 (추가만 가능)
+-- 퍼지된 사용자의 이력도 남도록 users를 참조하지 않음
+CREATE TABLE user_audit (
+    id      INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    action  TEXT    NOT NULL,  -- created, updated, deleted, restored, purged
+    actor   TEXT,              -- JWT sub (토큰 없이 요청했으면 NULL)
+    version INTEGER,           -- 변경 후 버전 (purged는 NULL)
+    changes TEXT    NOT NULL,  -- {"name": {"before": "Alice", "after": "Alicia"}}
+    at      TEXT    NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+CREATE INDEX idx_user_audit_user ON user_audit (user_id, id);
+
+CREATE TRIGGER user_audit_no_update BEFORE UPDATE ON user_audit
+BEGIN
+    SELECT RAISE(ABORT, 'user_audit is append-only');
+END;
+
+CREATE TRIGGER user_audit_no_delete BEFORE DELETE ON user_audit
+BEGIN
+    SELECT RAISE(ABORT, 'user_audit is append-only');
+END;
@@ -0,0 +1,8 @@
+-- 생성 시각 (검색의 created_after 필터)
+-- ALTER TABLE은 상수 기본값만 허용 → 기존 행은 감사 로그의 created 시각(없으면 지금)으로 채움
+ALTER TABLE users ADD COLUMN created_at TEXT NOT NULL DEFAULT '';
+
+UPDATE users SET created_at = COALESCE(
+    (SELECT MIN(at) FROM user_audit WHERE user_audit.user_id = users.id AND action = 'created'),
+    strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+);
@@ -0,0 +1,7 @@
+-- 지운 값은 되돌릴 수 없음, 트리거만 0004로 되돌림
+DROP TRIGGER user_audit_no_update;
+
+CREATE TRIGGER user_audit_no_update BEFORE UPDATE ON user_audit
+BEGIN
+    SELECT RAISE(ABORT, 'user_audit is append-only');
+END;
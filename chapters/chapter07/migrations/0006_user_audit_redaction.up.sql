@@ -0,0 +1,24 @@
+-- 퍼지할 때 이력의 개인정보(이름, 이메일)도 지움
+-- 이력은 여전히 추가만 가능, 예외는 하나: users에 더 이상 없는 사용자의 changes를
+-- 값만 지운 형태({"name": "redacted", ...})로 바꾸는 것 (어떤 필드가 바뀌었는지는 남음)
+DROP TRIGGER user_audit_no_update;
+
+CREATE TRIGGER user_audit_no_update BEFORE UPDATE ON user_audit
+WHEN NOT (
+    NEW.id = OLD.id
+    AND NEW.user_id = OLD.user_id
+    AND NEW.action = OLD.action
+    AND NEW.actor IS OLD.actor
+    AND NEW.version IS OLD.version
+    AND NEW.at = OLD.at
+    AND NEW.changes = (SELECT json_group_object(key, 'redacted') FROM json_each(OLD.changes))
+    AND NOT EXISTS (SELECT 1 FROM users WHERE users.id = OLD.user_id)
+)
+BEGIN
+    SELECT RAISE(ABORT, 'user_audit is append-only');
+END;
+
+-- 이미 퍼지된 사용자의 이력
+UPDATE user_audit
+SET changes = (SELECT json_group_object(key, 'redacted') FROM json_each(user_audit.changes))
+WHERE NOT EXISTS (SELECT 1 FROM users WHERE users.id = user_audit.user_id);